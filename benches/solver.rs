@@ -0,0 +1,216 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use sudoku::bitboard::Candidates;
+use sudoku::brute_force;
+use sudoku::generator::Generator;
+use sudoku::rating::{self, Difficulty};
+use sudoku::solver::{GenerateBasicMarkingsStep, NakedSingleStep, SolverStep};
+use sudoku::sudoku::{Game, Numbers};
+
+/// the steps as they worked on the notes of a [`Game`] before the bitboards, to compare against
+mod notes {
+    use log::info;
+    use sudoku::sudoku::Game;
+
+    pub fn basic_markings(state: Game) -> Game {
+        let mut seen_numbers = [0u16; 27];
+        // 27 = 9 rows + 9 cols + 9 groups
+        for row in 0..9 {
+            for cell in 0..9 {
+                let value = state.get(row + 1, cell + 1);
+                if value > 0 {
+                    info!("add seen digit: {}: {}", value, 1u16 << (value - 1));
+                    let box_id = (row / 3) * 3 + (cell / 3);
+                    seen_numbers[row] += 1u16 << (value - 1) as i16;
+                    seen_numbers[9 + cell] += 1u16 << (value - 1) as i16;
+                    seen_numbers[18 + box_id] += 1u16 << (value - 1) as i16;
+                }
+            }
+        }
+
+        let mut new_game = state;
+        for idx in 0..81 {
+            let (row, col) = Game::cell_index_to_coords(idx);
+            let value = state.get(row, col);
+
+            if value == 0 {
+                let box_id = ((row - 1) / 3) * 3 + ((col - 1) / 3);
+                new_game.set_notes(
+                    row,
+                    col,
+                    0x01FF
+                        & !seen_numbers[row - 1]
+                        & !seen_numbers[9 + col - 1]
+                        & !seen_numbers[18 + box_id],
+                )
+            }
+        }
+        new_game
+    }
+
+    pub fn naked_singles(state: Game) -> Game {
+        let mut new_game = state;
+        for idx in 0..81 {
+            let (row, col) = Game::cell_index_to_coords(idx);
+            let notes = state.get_notes(row, col);
+            if state.get(row, col) == 0 && notes.count_ones() == 1 {
+                new_game.set(row, col, notes.trailing_zeros() as u8 + 1);
+            }
+        }
+
+        new_game
+    }
+}
+
+/// the puzzle sets in `benches/puzzles`, by name
+fn puzzle_sets() -> Vec<(&'static str, Vec<Numbers>)> {
+    let sets = [
+        ("easy", include_str!("puzzles/easy.txt")),
+        ("hard", include_str!("puzzles/hard.txt")),
+        ("17-clue", include_str!("puzzles/17-clue.txt")),
+        ("pathological", include_str!("puzzles/pathological.txt")),
+    ];
+
+    sets.into_iter()
+        .map(|(name, content)| {
+            let puzzles = content
+                .lines()
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(|line| line.parse().expect("puzzle sets only contain valid lines"))
+                .collect();
+            (name, puzzles)
+        })
+        .collect()
+}
+
+/// benchmarks `f` once per puzzle set, reporting the throughput in puzzles
+fn bench_sets<T>(
+    c: &mut Criterion,
+    group_name: &str,
+    prepare: impl Fn(&Numbers) -> T,
+    f: impl Fn(&T),
+) {
+    let mut group = c.benchmark_group(group_name);
+    for (name, puzzles) in puzzle_sets() {
+        let inputs: Vec<T> = puzzles.iter().map(&prepare).collect();
+        group.throughput(Throughput::Elements(inputs.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &inputs, |b, inputs| {
+            b.iter(|| inputs.iter().for_each(|input| f(black_box(input))))
+        });
+    }
+    group.finish();
+}
+
+fn is_valid(c: &mut Criterion) {
+    bench_sets(
+        c,
+        "is_valid",
+        |n| Game::create(*n),
+        |game| {
+            black_box(game.is_valid());
+        },
+    );
+}
+
+fn basic_markings(c: &mut Criterion) {
+    bench_sets(
+        c,
+        "basic_markings",
+        |n| Game::create(*n),
+        |game| {
+            black_box(GenerateBasicMarkingsStep.apply(*game));
+        },
+    );
+}
+
+/// placing naked singles until there are none left, on the notes of a game as before the
+/// bitboards, which have to be marked again after every round, and on the bitboards
+fn naked_singles_solve(c: &mut Criterion) {
+    bench_sets(
+        c,
+        "naked_singles_solve/notes",
+        |n| Game::create(*n),
+        |game| {
+            let mut game = *game;
+            loop {
+                let next = notes::naked_singles(notes::basic_markings(game));
+                if next == game {
+                    break;
+                }
+                game = next;
+            }
+            black_box(game);
+        },
+    );
+    bench_sets(
+        c,
+        "naked_singles_solve/bitboards",
+        Candidates::from_numbers,
+        |candidates| {
+            let mut candidates = *candidates;
+            while NakedSingleStep.apply_candidates(&mut candidates) {}
+            black_box(candidates);
+        },
+    );
+}
+
+fn techniques(c: &mut Criterion) {
+    for (step, _) in rating::techniques() {
+        bench_sets(
+            c,
+            &format!("technique/{:?}", step),
+            Candidates::from_numbers,
+            |candidates| {
+                let mut candidates = *candidates;
+                black_box(step.apply_candidates(&mut candidates));
+            },
+        );
+    }
+}
+
+fn logical_solve(c: &mut Criterion) {
+    bench_sets(
+        c,
+        "logical_solve",
+        |n| *n,
+        |puzzle| {
+            black_box(rating::rate(puzzle));
+        },
+    );
+}
+
+fn brute_force_solve(c: &mut Criterion) {
+    bench_sets(
+        c,
+        "brute_force",
+        |n| *n,
+        |puzzle| {
+            black_box(brute_force::solve(puzzle));
+        },
+    );
+}
+
+fn generate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generate");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(1));
+    for difficulty in [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard] {
+        let mut generator = Generator::from_seed(1);
+        group.bench_function(BenchmarkId::from_parameter(difficulty), |b| {
+            b.iter(|| black_box(generator.generate(Some(difficulty))))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    is_valid,
+    basic_markings,
+    naked_singles_solve,
+    techniques,
+    logical_solve,
+    brute_force_solve,
+    generate
+);
+criterion_main!(benches);
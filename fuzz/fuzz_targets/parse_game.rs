@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use sudoku::sudoku::Game;
+
+// run with `cargo +nightly fuzz run parse_game`
+fuzz_target!(|data: &[u8]| {
+    let Ok(line) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    if let Ok(game) = line.parse::<Game>() {
+        assert_eq!(game, game.to_string().parse().unwrap());
+    }
+});
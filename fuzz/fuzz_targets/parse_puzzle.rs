@@ -0,0 +1,19 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use sudoku::batch;
+use sudoku::sudoku::Numbers;
+
+// run with `cargo +nightly fuzz run parse_puzzle`
+fuzz_target!(|data: &[u8]| {
+    let Ok(line) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    if let Ok(numbers) = line.parse::<Numbers>() {
+        assert_eq!(numbers, numbers.to_string().parse().unwrap());
+    }
+
+    // malformed, conflicting or ambiguous puzzles have to end up as a `Problem`
+    let _ = batch::analyze(line);
+});
@@ -0,0 +1,18 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use sudoku::formats::Format;
+
+// run with `cargo +nightly fuzz run read_file`
+fuzz_target!(|data: &[u8]| {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    let _ = Format::guess(input);
+    for format in [Format::Line, Format::Sdk, Format::Ss, Format::PencilMarks] {
+        if let Ok(game) = format.read(input) {
+            assert_eq!(Ok(game), format.read(&format.write(&game)), "{:?}", format);
+        }
+    }
+});
@@ -0,0 +1,325 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, BufRead};
+use std::mem;
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::brute_force::{self, Solutions};
+use crate::rating::{self, Difficulty};
+use crate::sudoku::{Game, Numbers, ParseNumbersError};
+
+/// how many puzzles are handed to a worker at once
+const CHUNK_SIZE: usize = 256;
+
+/// what to do with every puzzle of a batch
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Task {
+    Solve,
+    Rate,
+    Check,
+}
+
+/// why a puzzle of a batch could not be processed
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Problem {
+    Unparsable(ParseNumbersError),
+    /// the givens already contain a duplicate in some unit
+    Conflicting,
+    Unsolvable,
+    Multiple,
+}
+
+impl Problem {
+    /// a single word describing the problem, as used by the `check` output
+    pub fn label(&self) -> &'static str {
+        match self {
+            Problem::Unparsable(_) | Problem::Conflicting => "invalid",
+            Problem::Unsolvable => "unsolvable",
+            Problem::Multiple => "multiple",
+        }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::Unparsable(e) => write!(f, "invalid puzzle: {}", e),
+            Problem::Conflicting => f.write_str("invalid puzzle: givens conflict with each other"),
+            Problem::Unsolvable => f.write_str("puzzle has no solution"),
+            Problem::Multiple => f.write_str("puzzle has more than one solution"),
+        }
+    }
+}
+
+/// the successful result of a [`Task`] for a single puzzle
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "outcome", rename_all = "lowercase")
+)]
+pub enum Outcome {
+    Solved {
+        solution: Numbers,
+    },
+    Rated {
+        puzzle: Numbers,
+        difficulty: Difficulty,
+    },
+    Unique {
+        puzzle: Numbers,
+    },
+}
+
+/// formats the outcome as an output line: the solution, or the puzzle followed by its rating
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Solved { solution } => write!(f, "{}", solution),
+            Outcome::Rated { puzzle, difficulty } => write!(f, "{} {}", puzzle, difficulty),
+            Outcome::Unique { puzzle } => write!(f, "{} unique", puzzle),
+        }
+    }
+}
+
+/// parses a puzzle and makes sure it has exactly one solution, which is returned with it
+pub fn analyze(line: &str) -> Result<(Numbers, Numbers), Problem> {
+    let puzzle: Numbers = line.parse().map_err(Problem::Unparsable)?;
+
+    if !Game::create(puzzle).is_valid() {
+        return Err(Problem::Conflicting);
+    }
+
+    match brute_force::solve(&puzzle) {
+        Solutions::Unique(solution) => Ok((puzzle, solution)),
+        Solutions::None => Err(Problem::Unsolvable),
+        Solutions::Multiple => Err(Problem::Multiple),
+    }
+}
+
+impl Task {
+    pub fn run(&self, line: &str) -> Result<Outcome, Problem> {
+        let (puzzle, solution) = analyze(line)?;
+        Ok(match self {
+            Task::Solve => Outcome::Solved { solution },
+            Task::Rate => Outcome::Rated {
+                difficulty: rating::rate(&puzzle),
+                puzzle,
+            },
+            Task::Check => Outcome::Unique { puzzle },
+        })
+    }
+}
+
+/// the result for one puzzle line of the input
+#[derive(Debug, PartialEq, Clone)]
+pub struct Record {
+    /// 1-based line number in the input
+    pub line_no: usize,
+    /// the puzzle as it was found in the input
+    pub puzzle: String,
+    pub result: Result<Outcome, Problem>,
+}
+
+/// throughput numbers of a finished batch
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Stats {
+    pub puzzles: usize,
+    pub failures: usize,
+    pub elapsed: Duration,
+}
+
+impl Stats {
+    pub fn puzzles_per_second(&self) -> f64 {
+        self.puzzles as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} puzzles ({} failed) in {:.3}s, {:.0} puzzles/s",
+            self.puzzles,
+            self.failures,
+            self.elapsed.as_secs_f64(),
+            self.puzzles_per_second()
+        )
+    }
+}
+
+/// buffers chunks that finished out of order until all chunks before them are written
+struct InOrder {
+    next: usize,
+    pending: BTreeMap<usize, Vec<Record>>,
+}
+
+impl InOrder {
+    fn push(
+        &mut self,
+        (seq, records): (usize, Vec<Record>),
+        stats: &mut Stats,
+        sink: &mut impl FnMut(Record) -> io::Result<()>,
+    ) -> io::Result<()> {
+        self.pending.insert(seq, records);
+        while let Some(records) = self.pending.remove(&self.next) {
+            self.next += 1;
+            for record in records {
+                stats.puzzles += 1;
+                if record.result.is_err() {
+                    stats.failures += 1;
+                }
+                sink(record)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// runs `task` on every puzzle line of `input` using `threads` worker threads and hands the
+/// records to `sink` in input order.
+///
+/// the input is streamed in chunks, and at most two chunks per thread are queued, being worked
+/// on or waiting for an earlier chunk at any time, so a slow puzzle holds up reading instead of
+/// piling up finished chunks in memory. empty lines and lines starting with `#` are skipped,
+/// and only the first whitespace separated field of a line is treated as the puzzle.
+pub fn process<R: BufRead>(
+    input: R,
+    task: Task,
+    threads: usize,
+    mut sink: impl FnMut(Record) -> io::Result<()>,
+) -> io::Result<Stats> {
+    let start = Instant::now();
+    let threads = threads.max(1);
+    let mut stats = Stats::default();
+
+    // both channels can hold every chunk of the window, so sending never blocks
+    let window = threads * 2;
+    let (chunk_tx, chunk_rx) = mpsc::sync_channel::<(usize, Vec<(usize, String)>)>(window);
+    let chunk_rx = Mutex::new(chunk_rx);
+    let (record_tx, record_rx) = mpsc::sync_channel(window);
+
+    thread::scope(|scope| -> io::Result<()> {
+        for _ in 0..threads {
+            let chunk_rx = &chunk_rx;
+            let record_tx = record_tx.clone();
+            scope.spawn(move || loop {
+                let next = chunk_rx.lock().unwrap().recv();
+                let Ok((seq, lines)) = next else { break };
+                let records = lines
+                    .into_iter()
+                    .map(|(line_no, puzzle)| Record {
+                        line_no,
+                        result: task.run(&puzzle),
+                        puzzle,
+                    })
+                    .collect();
+                if record_tx.send((seq, records)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(record_tx);
+
+        let mut in_order = InOrder {
+            next: 0,
+            pending: BTreeMap::new(),
+        };
+        let mut chunk = Vec::with_capacity(CHUNK_SIZE);
+        let mut seq = 0;
+        for (idx, line) in input.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let puzzle = line.split_whitespace().next().unwrap_or_default();
+            chunk.push((idx + 1, puzzle.to_string()));
+            if chunk.len() == CHUNK_SIZE {
+                while seq - in_order.next >= window {
+                    let records = record_rx
+                        .recv()
+                        .expect("a chunk of the window is unfinished");
+                    in_order.push(records, &mut stats, &mut sink)?;
+                }
+                debug_assert!(in_order.pending.len() < window);
+
+                // workers only stop once the channel is closed, so this can't fail
+                chunk_tx.send((seq, mem::take(&mut chunk))).unwrap();
+                seq += 1;
+                for records in record_rx.try_iter() {
+                    in_order.push(records, &mut stats, &mut sink)?;
+                }
+            }
+        }
+
+        if !chunk.is_empty() {
+            chunk_tx.send((seq, chunk)).unwrap();
+        }
+        drop(chunk_tx);
+
+        for records in record_rx {
+            in_order.push(records, &mut stats, &mut sink)?;
+        }
+
+        Ok(())
+    })?;
+
+    stats.elapsed = start.elapsed();
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PUZZLE: &str =
+        "1...6....98....6.5.....5..1......3.4.6.13.9...4.72.....93.761....648...75..9.246.";
+
+    #[test]
+    fn keeps_input_order_across_threads() {
+        let mut input = String::from("# a comment\n\n");
+        let expected = 3 * CHUNK_SIZE + 7;
+        for i in 0..expected {
+            if i % 100 == 0 {
+                input.push_str("not a puzzle\n");
+            } else {
+                input.push_str(PUZZLE);
+                input.push_str(" some trailing text\n");
+            }
+        }
+
+        for threads in [1, 4] {
+            let mut records = vec![];
+            let stats = process(input.as_bytes(), Task::Check, threads, |record| {
+                records.push(record);
+                Ok(())
+            })
+            .unwrap();
+
+            assert_eq!(expected, stats.puzzles);
+            assert_eq!(expected.div_ceil(100), stats.failures);
+            for (i, record) in records.iter().enumerate() {
+                assert_eq!(i + 3, record.line_no);
+                assert_eq!(i % 100 == 0, record.result.is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn classifies_problems() {
+        assert_eq!(Err(Problem::Multiple), Task::Solve.run(&".".repeat(81)));
+        assert_eq!(
+            Err(Problem::Conflicting),
+            Task::Solve.run(&format!("11{}", ".".repeat(79)))
+        );
+        assert!(matches!(
+            Task::Solve.run("123"),
+            Err(Problem::Unparsable(_))
+        ));
+    }
+}
@@ -0,0 +1,343 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::process::ExitCode;
+use std::thread;
+use std::time::Duration;
+
+use sudoku::batch::{self, Outcome, Problem, Task};
+use sudoku::generator::{Budget, Generator, Profile};
+use sudoku::rating::Difficulty;
+use sudoku::render;
+
+const USAGE: &str = "\
+usage: sudoku <command> [options] [FILE...]
+
+puzzles are read from the given files, or stdin if there are none (or for `-`), one per line
+in the 81 character line format (digits for givens, `.` or `0` for empty cells). empty lines
+and lines starting with `#` are skipped, anything after the first whitespace is ignored.
+results are printed in input order.
+
+commands:
+  solve [FILE...]     print the solution of each puzzle
+  rate [FILE...]      print each puzzle followed by its difficulty
+  check [FILE...]     print each puzzle followed by unique, multiple, unsolvable or invalid
+  generate [-n COUNT] [--seed SEED] [--difficulty easy|medium|hard|expert] [--minimal]
+                      print COUNT (default 1) new puzzles, with --minimal only puzzles
+                      where every given is needed
+  generate --require TECHNIQUE [--max-technique TECHNIQUE] [--attempts N] [--time SECONDS]
+           [-n COUNT] [--seed SEED] [--minimal]
+                      print puzzles that need each required technique (the option can be
+                      repeated) and nothing harder than the maximum (default: the hardest
+                      required one). techniques are named like `X-Wing` or `naked pair`. gives
+                      up after N puzzles or SECONDS per puzzle and reports the closest one. the
+                      limits are checked between puzzles, so SECONDS may be overrun
+  print [-o FILE] [FILE...]
+                      write a PDF booklet of the puzzles with answer keys to FILE or stdout
+
+options for solve, rate, check and print:
+  -j, --threads N     number of worker threads (default: number of cpus)
+  --stats             print throughput statistics to stderr
+
+exit codes:
+  0  success
+  1  usage or i/o error
+  2  a puzzle was invalid
+  3  a puzzle had no solution
+  4  a puzzle had more than one solution
+  5  no puzzle matching the required techniques was found
+(if several puzzles fail, the code of the first failure is used)";
+
+/// options shared by the commands that process puzzle files
+struct BatchArgs {
+    files: Vec<String>,
+    threads: usize,
+    stats: bool,
+}
+
+impl BatchArgs {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut batch = BatchArgs {
+            files: vec![],
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            stats: false,
+        };
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-j" | "--threads" => {
+                    batch.threads = args
+                        .next()
+                        .ok_or_else(|| format!("missing value for {}", arg))?
+                        .parse()
+                        .map_err(|e| format!("invalid thread count: {}", e))?
+                }
+                "--stats" => batch.stats = true,
+                _ if arg.starts_with('-') && arg != "-" => {
+                    return Err(format!("unknown option {}", arg))
+                }
+                _ => batch.files.push(arg.clone()),
+            }
+        }
+
+        Ok(batch)
+    }
+}
+
+fn exit_code(problem: &Problem) -> u8 {
+    match problem {
+        Problem::Unparsable(_) | Problem::Conflicting => 2,
+        Problem::Unsolvable => 3,
+        Problem::Multiple => 4,
+    }
+}
+
+fn open_input(name: &str) -> Result<Box<dyn BufRead>, String> {
+    if name == "-" {
+        Ok(Box::new(io::stdin().lock()))
+    } else {
+        let file = File::open(name).map_err(|e| format!("{}: {}", name, e))?;
+        Ok(Box::new(BufReader::new(file)))
+    }
+}
+
+/// runs `task` on every input file (or stdin) and prints the results in input order
+fn run_batch(task: Task, args: &[String]) -> Result<ExitCode, String> {
+    let args = BatchArgs::parse(args)?;
+    let inputs: Vec<String> = if args.files.is_empty() {
+        vec!["-".into()]
+    } else {
+        args.files
+    };
+
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let mut exit = 0;
+
+    for name in inputs {
+        let input = open_input(&name)?;
+        let stats = batch::process(input, task, args.threads, |record| {
+            match (&record.result, task) {
+                (Ok(outcome), _) => return writeln!(out, "{}", outcome),
+                (Err(problem), Task::Check) => {
+                    writeln!(out, "{} {}", record.puzzle, problem.label())?
+                }
+                (Err(_), _) => {}
+            }
+
+            let problem = record.result.unwrap_err();
+            eprintln!("{}:{}: {}", name, record.line_no, problem);
+            if exit == 0 {
+                exit = exit_code(&problem);
+            }
+            Ok(())
+        })
+        .map_err(|e| format!("{}: {}", name, e))?;
+
+        if args.stats {
+            out.flush().map_err(|e| e.to_string())?;
+            eprintln!("{}: {}", name, stats);
+        }
+    }
+
+    out.flush().map_err(|e| e.to_string())?;
+    Ok(ExitCode::from(exit))
+}
+
+/// solves every input puzzle and writes them with their solutions as a PDF booklet. puzzles
+/// without a unique solution are reported and left out.
+fn print(args: &[String]) -> Result<ExitCode, String> {
+    let mut output = None;
+    let mut rest = vec![];
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-o" | "--output" => {
+                output = Some(
+                    iter.next()
+                        .ok_or_else(|| format!("missing value for {}", arg))?,
+                )
+            }
+            _ => rest.push(arg.clone()),
+        }
+    }
+
+    let args = BatchArgs::parse(&rest)?;
+    let inputs: Vec<String> = if args.files.is_empty() {
+        vec!["-".into()]
+    } else {
+        args.files
+    };
+
+    let mut puzzles = vec![];
+    let mut exit = 0;
+    for name in inputs {
+        let input = open_input(&name)?;
+        let stats = batch::process(input, Task::Solve, args.threads, |record| {
+            match record.result {
+                Ok(Outcome::Solved { solution }) => {
+                    let puzzle = record.puzzle.parse().expect("solved puzzles parse");
+                    puzzles.push((puzzle, solution));
+                }
+                Ok(_) => unreachable!("solving only has solved outcomes"),
+                Err(problem) => {
+                    eprintln!("{}:{}: {}", name, record.line_no, problem);
+                    if exit == 0 {
+                        exit = exit_code(&problem);
+                    }
+                }
+            }
+            Ok(())
+        })
+        .map_err(|e| format!("{}: {}", name, e))?;
+
+        if args.stats {
+            eprintln!("{}: {}", name, stats);
+        }
+    }
+
+    let pdf = render::booklet(&puzzles);
+    match output {
+        Some(path) => std::fs::write(path, pdf).map_err(|e| format!("{}: {}", path, e))?,
+        None => io::stdout().write_all(&pdf).map_err(|e| e.to_string())?,
+    }
+
+    Ok(ExitCode::from(exit))
+}
+
+fn generate(args: &[String]) -> Result<ExitCode, String> {
+    let mut count = 1usize;
+    let mut seed = None;
+    let mut difficulty = None;
+    let mut minimal = false;
+    let mut required = vec![];
+    let mut max_technique = None;
+    let mut budget = Budget::default();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("missing value for {}", arg))
+        };
+
+        match arg.as_str() {
+            "-n" | "--count" => {
+                count = value()?
+                    .parse()
+                    .map_err(|e| format!("invalid count: {}", e))?
+            }
+            "--seed" => {
+                seed = Some(
+                    value()?
+                        .parse()
+                        .map_err(|e| format!("invalid seed: {}", e))?,
+                )
+            }
+            "--difficulty" => match value()?.parse::<Difficulty>()? {
+                // hardly any random puzzle is beyond the techniques, so the search would
+                // run for a very long time
+                Difficulty::Unknown => {
+                    return Err("can't generate puzzles of unknown difficulty".into())
+                }
+                parsed => difficulty = Some(parsed),
+            },
+            "--minimal" => minimal = true,
+            "--require" => required.push(value()?.as_str()),
+            "--max-technique" => max_technique = Some(value()?.as_str()),
+            "--attempts" => {
+                budget.attempts = Some(
+                    value()?
+                        .parse()
+                        .map_err(|e| format!("invalid attempts: {}", e))?,
+                )
+            }
+            "--time" => {
+                budget.time = Some(Duration::from_secs_f64(
+                    value()?
+                        .parse()
+                        .map_err(|e| format!("invalid time: {}", e))?,
+                ))
+            }
+            _ => return Err(format!("unknown option {}", arg)),
+        }
+    }
+
+    let mut generator = seed.map(Generator::from_seed).unwrap_or_default();
+    generator.set_minimal(minimal);
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    if required.is_empty() && max_technique.is_none() {
+        for _ in 0..count {
+            writeln!(out, "{}", generator.generate(difficulty)).map_err(|e| e.to_string())?;
+        }
+
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if difficulty.is_some() {
+        return Err("--difficulty can't be combined with required techniques".into());
+    }
+    let profile = Profile::new(&required, max_technique)?;
+    for _ in 0..count {
+        let search = generator.generate_profile(&profile, budget);
+        if let Some(puzzle) = search.found() {
+            writeln!(out, "{}", puzzle).map_err(|e| e.to_string())?;
+            continue;
+        }
+
+        eprintln!(
+            "sudoku: no puzzle found in {} attempts ({:.1}s)",
+            search.attempts,
+            search.elapsed.as_secs_f64()
+        );
+        if let Some(best) = search.best {
+            if search.not_minimal {
+                eprintln!(
+                    "closest: {} uses {}, but is not minimal",
+                    best,
+                    search.used.join(", ")
+                );
+            } else {
+                eprintln!(
+                    "closest: {} uses {}, missing {}",
+                    best,
+                    search.used.join(", "),
+                    search.missing.join(", ")
+                );
+            }
+        }
+        return Ok(ExitCode::from(5));
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (command, rest) = match args.split_first() {
+        Some((command, rest)) => (command.as_str(), rest),
+        None => {
+            eprintln!("{}", USAGE);
+            return ExitCode::from(1);
+        }
+    };
+
+    let result = match command {
+        "solve" => run_batch(Task::Solve, rest),
+        "rate" => run_batch(Task::Rate, rest),
+        "check" => run_batch(Task::Check, rest),
+        "generate" => generate(rest),
+        "print" => print(rest),
+        "-h" | "--help" | "help" => {
+            println!("{}", USAGE);
+            Ok(ExitCode::SUCCESS)
+        }
+        _ => Err(format!("unknown command {}\n\n{}", command, USAGE)),
+    };
+
+    result.unwrap_or_else(|e| {
+        eprintln!("sudoku: {}", e);
+        ExitCode::from(1)
+    })
+}
@@ -0,0 +1,388 @@
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not};
+
+use crate::sudoku::{Game, Numbers};
+
+/// a set of cells, one bit per cell index in the lower 81 bits
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Hash)]
+pub struct Bitboard(u128);
+
+impl Bitboard {
+    pub const EMPTY: Bitboard = Bitboard(0);
+    pub const ALL: Bitboard = Bitboard((1 << 81) - 1);
+
+    pub const fn cell(idx: usize) -> Bitboard {
+        Bitboard(1 << idx)
+    }
+
+    pub const fn contains(self, idx: usize) -> bool {
+        self.0 & (1 << idx) != 0
+    }
+
+    pub fn insert(&mut self, idx: usize) {
+        self.0 |= 1 << idx;
+    }
+
+    pub fn remove(&mut self, idx: usize) {
+        self.0 &= !(1 << idx);
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn count(self) -> u32 {
+        self.0.count_ones()
+    }
+
+    /// the lowest cell index in the set
+    pub const fn first(self) -> Option<usize> {
+        if self.0 == 0 {
+            None
+        } else {
+            Some(self.0.trailing_zeros() as usize)
+        }
+    }
+
+    pub fn iter(self) -> BitboardIter {
+        BitboardIter(self.0)
+    }
+}
+
+impl BitAnd for Bitboard {
+    type Output = Bitboard;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Bitboard(self.0 & rhs.0)
+    }
+}
+
+impl BitAndAssign for Bitboard {
+    fn bitand_assign(&mut self, rhs: Self) {
+        self.0 &= rhs.0;
+    }
+}
+
+impl BitOr for Bitboard {
+    type Output = Bitboard;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Bitboard(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Bitboard {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// the complement within the 81 cells of the grid
+impl Not for Bitboard {
+    type Output = Bitboard;
+
+    fn not(self) -> Self::Output {
+        Bitboard(!self.0 & Self::ALL.0)
+    }
+}
+
+/// iterates the cell indices of a [`Bitboard`] in ascending order
+pub struct BitboardIter(u128);
+
+impl Iterator for BitboardIter {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0 == 0 {
+            return None;
+        }
+
+        let idx = self.0.trailing_zeros() as usize;
+        self.0 &= self.0 - 1;
+        Some(idx)
+    }
+}
+
+impl IntoIterator for Bitboard {
+    type Item = usize;
+    type IntoIter = BitboardIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+const fn house_cell(house: usize, i: usize) -> usize {
+    match house {
+        0..=8 => house * 9 + i,
+        9..=17 => i * 9 + (house - 9),
+        _ => {
+            let box_id = house - 18;
+            (box_id / 3) * 27 + (box_id % 3) * 3 + (i / 3) * 9 + (i % 3)
+        }
+    }
+}
+
+const fn compute_houses() -> [Bitboard; 27] {
+    let mut houses = [Bitboard::EMPTY; 27];
+    let mut house = 0;
+    while house < 27 {
+        let mut i = 0;
+        while i < 9 {
+            houses[house].0 |= 1 << house_cell(house, i);
+            i += 1;
+        }
+        house += 1;
+    }
+
+    houses
+}
+
+/// the 27 houses (units) of the grid: 0-8 are the rows, 9-17 the cols and 18-26 the boxes
+pub const HOUSES: [Bitboard; 27] = compute_houses();
+
+/// the row, col and box house index of a cell
+pub const fn houses_of(idx: usize) -> [usize; 3] {
+    let row = idx / 9;
+    let col = idx % 9;
+    [row, 9 + col, 18 + (row / 3) * 3 + col / 3]
+}
+
+const fn compute_peers() -> [Bitboard; 81] {
+    let mut peers = [Bitboard::EMPTY; 81];
+    let mut idx = 0;
+    while idx < 81 {
+        let [row, col, sbox] = houses_of(idx);
+        peers[idx].0 = (HOUSES[row].0 | HOUSES[col].0 | HOUSES[sbox].0) & !(1 << idx);
+        idx += 1;
+    }
+
+    peers
+}
+
+/// the 20 cells that share a house with a cell, not including the cell itself
+pub const PEERS: [Bitboard; 81] = compute_peers();
+
+/// the candidate state of a whole grid, stored as one bitboard per digit.
+///
+/// this is the representation the solver steps work on, as it answers questions like "where in
+/// this box can a 5 go" with a single `&`. [`Candidates::from_game`] and
+/// [`Candidates::apply_to`] convert from and to the notes of a [`Game`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Candidates {
+    /// for each digit, the unsolved cells that may still hold it
+    digits: [Bitboard; 9],
+    values: [u8; 81],
+    givens: Bitboard,
+}
+
+/// the digits of the cells, where values above 9 count as empty like in [`Numbers::value`]
+fn placed(numbers: &Numbers) -> [u8; 81] {
+    std::array::from_fn(|idx| match numbers.get_by_offset(idx) {
+        value @ 1..=9 => value,
+        _ => 0,
+    })
+}
+
+impl Candidates {
+    /// the candidates left after eliminating every placed digit from its peers, all of which
+    /// count as givens
+    pub fn from_numbers(numbers: &Numbers) -> Self {
+        let mut candidates = Candidates {
+            digits: [Bitboard::ALL; 9],
+            values: placed(numbers),
+            givens: Bitboard::EMPTY,
+        };
+
+        for (idx, value) in candidates.values.into_iter().enumerate() {
+            if value != 0 {
+                candidates.givens.insert(idx);
+                for digit in candidates.digits.iter_mut() {
+                    digit.remove(idx);
+                }
+                candidates.digits[value as usize - 1] &= !PEERS[idx];
+            }
+        }
+
+        candidates
+    }
+
+    /// takes the notes of the game as they are, so unmarked cells have no candidates
+    pub fn from_game(game: &Game) -> Self {
+        let mut candidates = Candidates {
+            digits: [Bitboard::EMPTY; 9],
+            values: placed(&game.current_numbers()),
+            givens: Bitboard::EMPTY,
+        };
+
+        for idx in 0..81 {
+            if game.index_is_given(idx) && candidates.values[idx] != 0 {
+                candidates.givens.insert(idx);
+            }
+            if candidates.values[idx] == 0 {
+                let (row, col) = Game::cell_index_to_coords(idx);
+                let notes = game.get_notes(row, col);
+                for (d, digit) in candidates.digits.iter_mut().enumerate() {
+                    if notes & (1 << d) != 0 {
+                        digit.insert(idx);
+                    }
+                }
+            }
+        }
+
+        candidates
+    }
+
+    /// writes the placed digits and the remaining candidates of unsolved cells into the game
+    pub fn apply_to(&self, game: Game) -> Game {
+        let mut game = game;
+        for idx in 0..81 {
+            let (row, col) = Game::cell_index_to_coords(idx);
+            if game.get(row, col) != 0 {
+                continue;
+            }
+
+            match self.values[idx] {
+                0 => game.set_notes(row, col, self.cell(idx)),
+                value => {
+                    game.set(row, col, value);
+                }
+            }
+        }
+
+        game
+    }
+
+    /// the placed digit of a cell, or 0
+    pub fn value(&self, idx: usize) -> u8 {
+        self.values[idx]
+    }
+
+    pub fn values(&self) -> Numbers {
+        Numbers::from(self.values)
+    }
+
+    /// the candidates of a cell in the same layout as the notes of a [`Game`]
+    pub fn cell(&self, idx: usize) -> u16 {
+        let mut notes = 0;
+        for (d, digit) in self.digits.iter().enumerate() {
+            if digit.contains(idx) {
+                notes |= 1 << d;
+            }
+        }
+
+        notes
+    }
+
+    /// the cells where `digit` (1-9) is still a candidate
+    pub fn digit(&self, digit: u8) -> Bitboard {
+        self.digits[digit as usize - 1]
+    }
+
+    /// the cells whose digit is part of the puzzle, rather than placed while solving
+    pub fn givens(&self) -> Bitboard {
+        self.givens
+    }
+
+    /// the cells without a placed digit
+    pub fn unsolved(&self) -> Bitboard {
+        let mut unsolved = Bitboard::EMPTY;
+        for (idx, &value) in self.values.iter().enumerate() {
+            if value == 0 {
+                unsolved.insert(idx);
+            }
+        }
+
+        unsolved
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.values.iter().all(|&value| value != 0)
+    }
+
+    /// unsolved cells with exactly one candidate, which are found with a bit-sliced count
+    pub fn naked_singles(&self) -> Bitboard {
+        let mut once = Bitboard::EMPTY;
+        let mut more = Bitboard::EMPTY;
+        for &digit in &self.digits {
+            more |= once & digit;
+            once |= digit;
+        }
+
+        once & !more
+    }
+
+    /// places a digit and removes it from the candidates of all peers
+    pub fn place(&mut self, idx: usize, digit: u8) {
+        self.values[idx] = digit;
+        for candidates in self.digits.iter_mut() {
+            candidates.remove(idx);
+        }
+        self.digits[digit as usize - 1] &= !PEERS[idx];
+    }
+
+    /// removes `digit` from the candidates of all given cells, returns whether any was removed
+    pub fn eliminate(&mut self, digit: u8, cells: Bitboard) -> bool {
+        let candidates = &mut self.digits[digit as usize - 1];
+        let changed = !(*candidates & cells).is_empty();
+        *candidates &= !cells;
+        changed
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PUZZLE: &str =
+        "1...6....98....6.5.....5..1......3.4.6.13.9...4.72.....93.761....648...75..9.246.";
+
+    #[test]
+    fn houses_and_peers_have_the_right_size() {
+        assert!(HOUSES.iter().all(|house| house.count() == 9));
+        assert!(PEERS.iter().all(|peers| peers.count() == 20));
+        assert!(PEERS[0].contains(72) && !PEERS[0].contains(80));
+    }
+
+    #[test]
+    fn iterates_cells_in_order() {
+        let board = Bitboard::cell(3) | Bitboard::cell(80) | Bitboard::cell(64);
+        assert_eq!(vec![3, 64, 80], board.iter().collect::<Vec<_>>());
+        assert_eq!(78, (!board).count());
+    }
+
+    #[test]
+    fn game_round_trips_through_candidates() {
+        let numbers: Numbers = PUZZLE.parse().unwrap();
+        let marked = Candidates::from_numbers(&numbers).apply_to(Game::create(numbers));
+
+        let candidates = Candidates::from_game(&marked);
+        assert_eq!(Candidates::from_numbers(&numbers), candidates);
+        assert_eq!(marked, candidates.apply_to(marked));
+    }
+
+    #[test]
+    fn placing_removes_the_digit_from_peers() {
+        let mut candidates = Candidates::from_numbers(&Numbers::empty());
+        candidates.place(40, 5);
+        assert_eq!(Bitboard::ALL.count() - 21, candidates.digit(5).count());
+        assert!(candidates.digit(1).count() == 80);
+        assert!(!candidates.naked_singles().contains(40));
+    }
+
+    #[test]
+    fn values_above_nine_are_empty() {
+        let numbers: Numbers = PUZZLE.parse().unwrap();
+        let mut cells: [u8; 81] = numbers.into();
+        cells[1] = 12;
+        let broken = Numbers::from(cells);
+
+        assert_eq!(
+            Candidates::from_numbers(&numbers),
+            Candidates::from_numbers(&broken)
+        );
+        let candidates = Candidates::from_game(&Game::create(broken));
+        assert_eq!(0, candidates.value(1));
+        assert_eq!(0, candidates.cell(1));
+        assert_eq!(crate::rating::rate(&numbers), crate::rating::rate(&broken));
+    }
+}
@@ -0,0 +1,217 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::sudoku::Numbers;
+
+/// the outcome of searching every possible completion of a puzzle
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "solutions", content = "solution", rename_all = "lowercase")
+)]
+pub enum Solutions {
+    /// the givens already conflict, or no completion exists
+    None,
+    Unique(Numbers),
+    /// at least two completions exist, so the puzzle is not a proper sudoku
+    Multiple,
+}
+
+/// finds the solutions of a puzzle by backtracking, stopping as soon as a second one shows up
+pub fn solve(numbers: &Numbers) -> Solutions {
+    let mut search = match Search::new(numbers) {
+        Some(search) => search,
+        None => return Solutions::None,
+    };
+
+    let mut first = None;
+    let mut count = 0;
+    search.run(&mut |_| {}, &mut |cells| {
+        count += 1;
+        first.get_or_insert(Numbers::from(*cells));
+        count < 2
+    });
+
+    match (count, first) {
+        (1, Some(solution)) => Solutions::Unique(solution),
+        (0, _) => Solutions::None,
+        _ => Solutions::Multiple,
+    }
+}
+
+/// creates a random, completely filled grid
+pub fn random_solution<R: Rng + ?Sized>(rng: &mut R) -> Numbers {
+    let mut search = Search::new(&Numbers::empty()).unwrap();
+    let mut solution = Numbers::empty();
+    search.run(&mut |digits| digits.shuffle(rng), &mut |cells| {
+        solution = Numbers::from(*cells);
+        false
+    });
+
+    solution
+}
+
+/// the state of a depth-first search, tracking the used digits of each unit as bitmasks
+struct Search {
+    cells: [u8; 81],
+    rows: [u16; 9],
+    cols: [u16; 9],
+    boxes: [u16; 9],
+}
+
+impl Search {
+    /// returns `None` if the given numbers already conflict with each other or are not digits
+    fn new(numbers: &Numbers) -> Option<Self> {
+        let mut search = Search {
+            cells: [0; 81],
+            rows: [0; 9],
+            cols: [0; 9],
+            boxes: [0; 9],
+        };
+
+        for (idx, value) in numbers.cells().enumerate() {
+            if value == 0 {
+                continue;
+            }
+
+            if value > 9 || search.candidates(idx) & (1 << (value - 1)) == 0 {
+                return None;
+            }
+            search.place(idx, value);
+        }
+
+        Some(search)
+    }
+
+    fn units(idx: usize) -> (usize, usize, usize) {
+        let row = idx / 9;
+        let col = idx % 9;
+        (row, col, (row / 3) * 3 + col / 3)
+    }
+
+    fn candidates(&self, idx: usize) -> u16 {
+        let (row, col, sbox) = Self::units(idx);
+        0x01FF & !(self.rows[row] | self.cols[col] | self.boxes[sbox])
+    }
+
+    fn place(&mut self, idx: usize, value: u8) {
+        let (row, col, sbox) = Self::units(idx);
+        let mask = 1 << (value - 1);
+        self.cells[idx] = value;
+        self.rows[row] |= mask;
+        self.cols[col] |= mask;
+        self.boxes[sbox] |= mask;
+    }
+
+    fn remove(&mut self, idx: usize) {
+        let (row, col, sbox) = Self::units(idx);
+        let mask = !(1 << (self.cells[idx] - 1));
+        self.cells[idx] = 0;
+        self.rows[row] &= mask;
+        self.cols[col] &= mask;
+        self.boxes[sbox] &= mask;
+    }
+
+    /// the empty cell with the fewest candidates, or `None` if the grid is full
+    fn most_constrained_cell(&self) -> Option<(usize, u16)> {
+        let mut best: Option<(usize, u16)> = None;
+        for idx in 0..81 {
+            if self.cells[idx] != 0 {
+                continue;
+            }
+
+            let candidates = self.candidates(idx);
+            if best.is_none_or(|(_, b)| candidates.count_ones() < b.count_ones()) {
+                best = Some((idx, candidates));
+                if candidates.count_ones() <= 1 {
+                    break;
+                }
+            }
+        }
+
+        best
+    }
+
+    /// visits every solution until `found` returns false. `order` may reorder the candidate
+    /// digits of a cell before they are tried. returns false if the search was stopped early.
+    fn run(
+        &mut self,
+        order: &mut dyn FnMut(&mut [u8]),
+        found: &mut dyn FnMut(&[u8; 81]) -> bool,
+    ) -> bool {
+        let (idx, candidates) = match self.most_constrained_cell() {
+            Some(cell) => cell,
+            None => return found(&self.cells),
+        };
+
+        let mut digits = [0u8; 9];
+        let mut len = 0;
+        for digit in 1..=9u8 {
+            if candidates & (1 << (digit - 1)) > 0 {
+                digits[len] = digit;
+                len += 1;
+            }
+        }
+        order(&mut digits[..len]);
+
+        for &digit in &digits[..len] {
+            self.place(idx, digit);
+            let keep_going = self.run(order, found);
+            self.remove(idx);
+            if !keep_going {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::sudoku::Game;
+
+    #[test]
+    fn solves_unique_puzzle() {
+        let puzzle: Numbers =
+            "1...6....98....6.5.....5..1......3.4.6.13.9...4.72.....93.761....648...75..9.246."
+                .parse()
+                .unwrap();
+
+        match solve(&puzzle) {
+            Solutions::Unique(solution) => {
+                assert!(Game::create(solution).is_solved());
+                for (given, solved) in puzzle.cells().zip(solution.cells()) {
+                    assert!(given == 0 || given == solved);
+                }
+            }
+            other => panic!("expected a unique solution, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn detects_multiple_solutions() {
+        assert_eq!(Solutions::Multiple, solve(&Numbers::empty()));
+    }
+
+    #[test]
+    fn detects_conflicting_givens() {
+        let mut cells = [0u8; 81];
+        cells[0] = 5;
+        cells[1] = 5;
+        assert_eq!(Solutions::None, solve(&Numbers::from(cells)));
+    }
+
+    #[test]
+    fn random_solution_is_solved_and_seeded() {
+        let a = random_solution(&mut StdRng::seed_from_u64(7));
+        let b = random_solution(&mut StdRng::seed_from_u64(7));
+        assert!(Game::create(a).is_solved());
+        assert_eq!(a, b);
+    }
+}
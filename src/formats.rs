@@ -0,0 +1,518 @@
+use std::fmt;
+
+use crate::sudoku::{Game, Numbers, ParseNumbersError};
+
+/// the file formats a [`Game`] can be read from and written to
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Format {
+    /// a single line of 81 cells, see the `FromStr` impl of [`Numbers`]
+    Line,
+    /// SadMan Sudoku / Sudoku Dessert: `#` header lines, then a 9x9 grid. the grid may be in a
+    /// `[Puzzle]` section, followed by a `[State]` section with the current progress.
+    Sdk,
+    /// Simple Sudoku: a 9x9 grid with `|` between the boxes and `---` lines between the bands
+    Ss,
+    /// a pencil-mark grid as used by HoDoKu and in forum posts, where each cell lists its
+    /// candidates. cells with a single digit are read as givens, the others as notes. so the
+    /// other cells keep apart when written, a digit the player entered is written as `(5)`,
+    /// and notes of fewer than two digits in brackets, like `[5]` or `[]` for none.
+    PencilMarks,
+}
+
+/// reasons why a file could not be read into a [`Game`]. line numbers are 1-based.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ParseFileError {
+    Line(ParseNumbersError),
+    /// the grid row at the given line does not have exactly 9 cells
+    InvalidRow(usize),
+    InvalidCell(usize, char),
+    /// the grid has a different number of rows than 9
+    RowCount(usize),
+    UnknownSection(usize),
+    /// the progress state changes a given cell, at the given cell offset
+    StateConflict(usize),
+    /// a pencil-mark grid has a different number of cells than 81
+    CellCount(usize),
+}
+
+impl fmt::Display for ParseFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseFileError::Line(e) => write!(f, "{}", e),
+            ParseFileError::InvalidRow(line) => write!(f, "line {}: expected 9 cells", line),
+            ParseFileError::InvalidCell(line, c) => {
+                write!(f, "line {}: invalid cell {:?}", line, c)
+            }
+            ParseFileError::RowCount(rows) => write!(f, "expected 9 rows, found {}", rows),
+            ParseFileError::UnknownSection(line) => write!(f, "line {}: unknown section", line),
+            ParseFileError::StateConflict(offset) => {
+                write!(f, "state changes the given at cell {}", offset + 1)
+            }
+            ParseFileError::CellCount(cells) => write!(f, "expected 81 cells, found {}", cells),
+        }
+    }
+}
+
+impl std::error::Error for ParseFileError {}
+
+impl Format {
+    /// guesses the format from a file extension, without the leading dot
+    pub fn from_extension(extension: &str) -> Option<Format> {
+        match extension.to_ascii_lowercase().as_str() {
+            "txt" => Some(Format::Line),
+            "sdk" => Some(Format::Sdk),
+            "ss" => Some(Format::Ss),
+            _ => None,
+        }
+    }
+
+    /// guesses the format of pasted text: a single line, a pencil-mark grid or else a plain grid
+    pub fn guess(input: &str) -> Format {
+        let lines: Vec<&str> = input
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .collect();
+
+        if lines.len() == 1 && lines[0].len() == 81 {
+            Format::Line
+        } else if pencil_mark_tokens(input).count() == 81 {
+            Format::PencilMarks
+        } else {
+            Format::Sdk
+        }
+    }
+
+    pub fn read(self, input: &str) -> Result<Game, ParseFileError> {
+        match self {
+            Format::Line => {
+                let line = input
+                    .lines()
+                    .map(str::trim)
+                    .find(|line| !line.is_empty() && !line.starts_with('#'))
+                    .unwrap_or_default();
+                let numbers: Numbers = line.parse().map_err(ParseFileError::Line)?;
+                Ok(Game::create(numbers))
+            }
+            Format::Sdk => read_sdk(input),
+            Format::Ss => Ok(Game::create(read_grid(input.lines().enumerate())?)),
+            Format::PencilMarks => read_pencil_marks(input),
+        }
+    }
+
+    /// writes the game. [`Format::Sdk`] keeps the progress and [`Format::PencilMarks`] the
+    /// entries and the notes of the empty cells too. the others only write the givens.
+    pub fn write(self, game: &Game) -> String {
+        match self {
+            Format::Line => format!("{}\n", game.given_numbers()),
+            Format::Sdk => {
+                let givens = game.given_numbers();
+                let current = game.current_numbers();
+                if givens == current {
+                    write_grid(&givens, false)
+                } else {
+                    format!(
+                        "[Puzzle]\n{}[State]\n{}",
+                        write_grid(&givens, false),
+                        write_grid(&current, false)
+                    )
+                }
+            }
+            Format::Ss => write_grid(&game.given_numbers(), true),
+            Format::PencilMarks => write_pencil_marks(game),
+        }
+    }
+}
+
+/// collects the cells of a grid from numbered lines, skipping comments and box separators
+fn read_grid<'a, I>(lines: I) -> Result<Numbers, ParseFileError>
+where
+    I: Iterator<Item = (usize, &'a str)>,
+{
+    let mut cells = [0u8; 81];
+    let mut rows = 0;
+
+    for (idx, line) in lines {
+        let line_no = idx + 1;
+        let line = line.trim();
+        let is_separator = line.chars().all(|c| matches!(c, '-' | '+' | '|' | ' '));
+        if line.starts_with('#') || is_separator {
+            continue;
+        }
+
+        let row: Vec<char> = line.chars().filter(|&c| c != '|' && c != ' ').collect();
+        if row.len() != 9 {
+            return Err(ParseFileError::InvalidRow(line_no));
+        }
+        if rows == 9 {
+            return Err(ParseFileError::RowCount(10));
+        }
+
+        for (col, c) in row.into_iter().enumerate() {
+            cells[rows * 9 + col] = match c {
+                '.' | '0' | 'X' | 'x' => 0,
+                '1'..='9' => c as u8 - b'0',
+                _ => return Err(ParseFileError::InvalidCell(line_no, c)),
+            };
+        }
+        rows += 1;
+    }
+
+    if rows != 9 {
+        return Err(ParseFileError::RowCount(rows));
+    }
+
+    Ok(Numbers::from(cells))
+}
+
+fn read_sdk(input: &str) -> Result<Game, ParseFileError> {
+    let lines: Vec<(usize, &str)> = input.lines().enumerate().collect();
+    let section_start = |name: &str| lines.iter().position(|(_, l)| l.trim() == name);
+
+    for &(idx, line) in &lines {
+        let line = line.trim();
+        if line.starts_with('[') && line != "[Puzzle]" && line != "[State]" {
+            return Err(ParseFileError::UnknownSection(idx + 1));
+        }
+    }
+
+    let (puzzle, state) = match (section_start("[Puzzle]"), section_start("[State]")) {
+        (Some(puzzle), Some(state)) if puzzle < state => {
+            (&lines[puzzle + 1..state], Some(&lines[state + 1..]))
+        }
+        (Some(puzzle), None) => (&lines[puzzle + 1..], None),
+        (None, None) => (&lines[..], None),
+        (_, Some(state)) => return Err(ParseFileError::UnknownSection(state + 1)),
+    };
+
+    let givens = read_grid(puzzle.iter().copied())?;
+    let mut game = Game::create(givens);
+
+    if let Some(state) = state {
+        let current = read_grid(state.iter().copied())?;
+        for (idx, (given, value)) in givens.cells().zip(current.cells()).enumerate() {
+            if given != 0 && given != value {
+                return Err(ParseFileError::StateConflict(idx));
+            }
+
+            let (row, col) = Game::cell_index_to_coords(idx);
+            game.set(row, col, value);
+        }
+    }
+
+    Ok(game)
+}
+
+/// the cell tokens of a pencil-mark grid with their line number: everything that is not part
+/// of a border, on lines that are not comments
+fn pencil_mark_tokens(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim_start().starts_with('#'))
+        .flat_map(|(idx, line)| {
+            line.split(|c: char| c.is_whitespace() || ".-:'+|*".contains(c))
+                .filter(|token| !token.is_empty())
+                .map(move |token| (idx + 1, token))
+        })
+}
+
+/// what a single cell of a pencil-mark grid holds
+enum PencilMark {
+    Given(u8),
+    Entry(u8),
+    Notes(u16),
+}
+
+fn read_pencil_mark(line_no: usize, token: &str) -> Result<PencilMark, ParseFileError> {
+    let bracketed = |open: char, close: char| {
+        token
+            .strip_prefix(open)
+            .and_then(|rest| rest.strip_suffix(close))
+    };
+    let (digits, kind) = if let Some(inner) = bracketed('(', ')') {
+        (inner, '(')
+    } else if let Some(inner) = bracketed('[', ']') {
+        (inner, '[')
+    } else {
+        (token, ' ')
+    };
+
+    let mut notes = 0u16;
+    for c in digits.chars() {
+        match c {
+            '1'..='9' => notes |= 1 << (c as u8 - b'1'),
+            _ => return Err(ParseFileError::InvalidCell(line_no, c)),
+        }
+    }
+
+    let single = (digits.len() == 1).then(|| digits.as_bytes()[0] - b'0');
+    match (kind, single) {
+        ('(', Some(value)) => Ok(PencilMark::Entry(value)),
+        ('(', None) => Err(ParseFileError::InvalidCell(
+            line_no,
+            digits.chars().nth(1).unwrap_or(')'),
+        )),
+        (' ', Some(value)) => Ok(PencilMark::Given(value)),
+        (' ', None) if digits.is_empty() => Err(ParseFileError::InvalidCell(line_no, ' ')),
+        _ => Ok(PencilMark::Notes(notes)),
+    }
+}
+
+fn read_pencil_marks(input: &str) -> Result<Game, ParseFileError> {
+    let mut marks = vec![];
+    for (line_no, token) in pencil_mark_tokens(input) {
+        if marks.len() == 81 {
+            return Err(ParseFileError::CellCount(82));
+        }
+        marks.push(read_pencil_mark(line_no, token)?);
+    }
+
+    if marks.len() != 81 {
+        return Err(ParseFileError::CellCount(marks.len()));
+    }
+
+    let givens: Vec<u8> = marks
+        .iter()
+        .map(|mark| match mark {
+            PencilMark::Given(value) => *value,
+            _ => 0,
+        })
+        .collect();
+    let mut game = Game::create(<[u8; 81]>::try_from(givens).unwrap());
+    for (idx, mark) in marks.into_iter().enumerate() {
+        let (row, col) = Game::cell_index_to_coords(idx);
+        match mark {
+            PencilMark::Given(_) => {}
+            PencilMark::Entry(value) => {
+                game.set(row, col, value);
+            }
+            PencilMark::Notes(notes) => game.set_notes(row, col, notes),
+        }
+    }
+
+    Ok(game)
+}
+
+fn write_pencil_marks(game: &Game) -> String {
+    let tokens: Vec<String> = (0..81)
+        .map(|idx| {
+            let (row, col) = Game::cell_index_to_coords(idx);
+            let notes = game.get_notes(row, col);
+            let digits = || -> String {
+                (1..=9u8)
+                    .filter(|digit| notes & (1 << (digit - 1)) != 0)
+                    .map(|digit| char::from(b'0' + digit))
+                    .collect()
+            };
+            match game.get(row, col) {
+                0 if notes.count_ones() < 2 => format!("[{}]", digits()),
+                0 => digits(),
+                value if game.is_given(row, col) => value.to_string(),
+                value => format!("({})", value),
+            }
+        })
+        .collect();
+
+    let widths: Vec<usize> = (0..9)
+        .map(|col| (0..9).map(|row| tokens[row * 9 + col].len()).max().unwrap())
+        .collect();
+    // a box is padded by a space on each side, and its cells are separated by two spaces
+    let box_widths: Vec<usize> = widths
+        .chunks(3)
+        .map(|w| w.iter().sum::<usize>() + 6)
+        .collect();
+    let border = |left: char, middle: char, right: char| {
+        let segments: Vec<String> = box_widths.iter().map(|&w| "-".repeat(w)).collect();
+        format!("{}{}{}\n", left, segments.join(&middle.to_string()), right)
+    };
+
+    let mut out = border('.', '.', '.');
+    for row in 0..9 {
+        if row > 0 && row % 3 == 0 {
+            out.push_str(&border(':', '+', ':'));
+        }
+
+        out.push('|');
+        for stack in 0..3 {
+            let cells: Vec<String> = (stack * 3..stack * 3 + 3)
+                .map(|col| format!("{:<width$}", tokens[row * 9 + col], width = widths[col]))
+                .collect();
+            out.push_str(&format!(" {} |", cells.join("  ")));
+        }
+        out.push('\n');
+    }
+    out.push_str(&border('\'', '\'', '\''));
+
+    out
+}
+
+/// writes 9 lines of 9 cells, with box separators in the Simple Sudoku style if requested
+fn write_grid(numbers: &Numbers, separators: bool) -> String {
+    let line = numbers.to_string();
+    let mut out = String::with_capacity(9 * 12 + 24);
+
+    for row in 0..9 {
+        if separators && row > 0 && row % 3 == 0 {
+            out.push_str("-----------\n");
+        }
+
+        for col in 0..9 {
+            if separators && col > 0 && col % 3 == 0 {
+                out.push('|');
+            }
+            out.push_str(&line[row * 9 + col..row * 9 + col + 1]);
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solver::{GenerateBasicMarkingsStep, SolverStep};
+
+    const PUZZLE: &str =
+        "1...6....98....6.5.....5..1......3.4.6.13.9...4.72.....93.761....648...75..9.246.";
+
+    const SS: &str = "\
+1..|.6.|...
+98.|...|6.5
+...|..5|..1
+-----------
+...|...|3.4
+.6.|13.|9..
+.4.|72.|...
+-----------
+.93|.76|1..
+..6|48.|..7
+5..|9.2|46.
+";
+
+    #[test]
+    fn reads_and_writes_simple_sudoku() {
+        let game = Format::Ss.read(SS).unwrap();
+        assert_eq!(PUZZLE.parse::<Numbers>().unwrap(), game.given_numbers());
+        assert_eq!(SS, Format::Ss.write(&game));
+    }
+
+    #[test]
+    fn reads_and_writes_sdk_with_header_and_state() {
+        let givens: Numbers = PUZZLE.parse().unwrap();
+        let mut game = Game::create(givens);
+        assert_eq!(Ok(game), Format::Sdk.read(&Format::Sdk.write(&game)));
+
+        game.set(1, 2, 2);
+        let sdk = format!(
+            "#AThe Author\n#DA description\n{}",
+            Format::Sdk.write(&game)
+        );
+        assert!(sdk.contains("[Puzzle]\n1..") && sdk.contains("[State]\n12."));
+        assert_eq!(Ok(game), Format::Sdk.read(&sdk));
+    }
+
+    #[test]
+    fn sdk_state_may_not_change_givens() {
+        let grid = Format::Sdk.write(&Format::Ss.read(SS).unwrap());
+        let sdk = format!("[Puzzle]\n{}[State]\n2{}", grid, &grid[1..]);
+        assert_eq!(
+            Err(ParseFileError::StateConflict(0)),
+            Format::Sdk.read(&sdk)
+        );
+    }
+
+    /// the puzzle after basic markings, as it would be posted in a forum
+    const PENCIL_MARKS: &str = "\
+.---------------------.-----------------.-------------------.
+| 1      2357  2457   | 238  6    34789 | 278  234789  2389 |
+| 9      8     247    | 23   14   1347  | 6    2347    5    |
+| 23467  237   247    | 238  49   5     | 278  234789  1    |
+:---------------------+-----------------+-------------------:
+| 278    1257  125789 | 568  59   89    | 3    12578   4    |
+| 278    6     2578   | 1    3    48    | 9    2578    28   |
+| 38     4     1589   | 7    2    89    | 58   158     68   |
+:---------------------+-----------------+-------------------:
+| 248    9     3      | [5]  7    6     | 1    258     28   |
+| [2]    12    6      | 4    8    13    | 25   2359    7    |
+| 5      17    178    | 9    [1]  2     | 4    6       38   |
+'---------------------'-----------------'-------------------'
+";
+
+    #[test]
+    fn reads_pencil_marks() {
+        let game = Format::PencilMarks.read(PENCIL_MARKS).unwrap();
+        assert!(game.is_given(1, 1));
+        assert_eq!(0b0_0101_0110, game.get_notes(1, 2));
+        assert_eq!(0b0_1101_0011, game.get_notes(4, 8));
+        assert_eq!(
+            GenerateBasicMarkingsStep.apply(Game::create(PUZZLE.parse::<Numbers>().unwrap())),
+            game
+        );
+        assert_eq!(Format::PencilMarks, Format::guess(PENCIL_MARKS));
+    }
+
+    #[test]
+    fn pencil_marks_round_trip() {
+        let game = Format::PencilMarks.read(PENCIL_MARKS).unwrap();
+        let written = Format::PencilMarks.write(&game);
+        assert!(written.starts_with(".-----"));
+        assert_eq!(Ok(game), Format::PencilMarks.read(&written));
+        assert_eq!(
+            written,
+            Format::PencilMarks.write(&Format::PencilMarks.read(&written).unwrap())
+        );
+    }
+
+    #[test]
+    fn pencil_marks_keep_givens_entries_and_notes_apart() {
+        let mut game = Game::create(PUZZLE.parse::<Numbers>().unwrap());
+        game.set(1, 2, 2);
+        game.set_notes(1, 3, 0b0_0000_1000);
+        game.set_notes(1, 4, 0b0_0000_1100);
+
+        let written = Format::PencilMarks.write(&game);
+        assert_eq!(
+            Some("| 1   (2)  [4] | 34  6   [] | []  []  [] |"),
+            written.lines().nth(1)
+        );
+        assert_eq!(Ok(game), Format::PencilMarks.read(&written));
+    }
+
+    #[test]
+    fn guesses_formats() {
+        assert_eq!(Format::Line, Format::guess(PUZZLE));
+        assert_eq!(Format::Sdk, Format::guess(SS));
+        assert_eq!(Ok(Format::Ss.read(SS).unwrap()), Format::Sdk.read(SS));
+    }
+
+    #[test]
+    fn reports_broken_grids() {
+        assert_eq!(Err(ParseFileError::RowCount(3)), Format::Ss.read(&SS[..36]));
+        assert_eq!(
+            Err(ParseFileError::InvalidRow(1)),
+            Format::Ss.read("1..|.6.|..\n")
+        );
+        assert_eq!(
+            Err(ParseFileError::InvalidCell(1, 'a')),
+            Format::Sdk.read("a........\n")
+        );
+        assert_eq!(
+            Err(ParseFileError::UnknownSection(1)),
+            Format::Sdk.read("[Notes]\n")
+        );
+        assert_eq!(
+            Err(ParseFileError::CellCount(2)),
+            Format::PencilMarks.read("| 12 3 |")
+        );
+        assert_eq!(
+            Err(ParseFileError::InvalidCell(1, '0')),
+            Format::PencilMarks.read("| 102 |")
+        );
+        assert_eq!(
+            Err(ParseFileError::InvalidCell(1, '2')),
+            Format::PencilMarks.read(&"(12) ".repeat(81))
+        );
+    }
+}
@@ -0,0 +1,316 @@
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::brute_force::{self, Solutions};
+use crate::minimal;
+use crate::rating::{self, Difficulty};
+use crate::sudoku::Numbers;
+
+/// the techniques a puzzle must need, and the hardest one it may need, named as in
+/// [`rating::techniques`]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Profile {
+    /// indexes into [`rating::techniques`]
+    required: Vec<usize>,
+    max: usize,
+    assume_uniqueness: bool,
+}
+
+impl Profile {
+    /// a profile from technique names, which are matched ignoring case. without a `max`, the
+    /// hardest required technique is the hardest one allowed. the uniqueness techniques are
+    /// only allowed when one of them is required.
+    pub fn new(required: &[&str], max: Option<&str>) -> Result<Self, String> {
+        let techniques = rating::techniques();
+        let find = |name: &str| {
+            techniques
+                .iter()
+                .position(|(step, _)| step.name().eq_ignore_ascii_case(name))
+                .ok_or_else(|| format!("unknown technique {:?}", name))
+        };
+
+        let required = required
+            .iter()
+            .map(|name| find(name))
+            .collect::<Result<Vec<_>, _>>()?;
+        let max = match max {
+            Some(name) => find(name)?,
+            None => *required.iter().max().ok_or("no technique given")?,
+        };
+        if let Some(&harder) = required.iter().find(|&&idx| idx > max) {
+            return Err(format!(
+                "{} is harder than {}",
+                techniques[harder].0.name(),
+                techniques[max].0.name()
+            ));
+        }
+
+        let assume_uniqueness = required
+            .iter()
+            .any(|&idx| techniques[idx].0.needs_uniqueness());
+        Ok(Profile {
+            required,
+            max,
+            assume_uniqueness,
+        })
+    }
+
+    fn used(&self, numbers: &Numbers) -> Option<Vec<usize>> {
+        rating::techniques_used(numbers, self.max, self.assume_uniqueness)
+    }
+}
+
+/// how long [`Generator::generate_profile`] may search. without any limit it searches until it
+/// finds a puzzle.
+///
+/// both limits are checked between attempts, so an attempt that has started runs to the end
+/// and the search may take longer than `time` by up to one attempt.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Budget {
+    pub attempts: Option<usize>,
+    pub time: Option<Duration>,
+}
+
+/// what [`Generator::generate_profile`] found
+#[derive(Debug, PartialEq, Clone)]
+pub struct ProfileSearch {
+    /// the puzzle that came closest: it needs nothing beyond the maximum, and the fewest of the
+    /// required techniques are missing from its solve
+    pub best: Option<Numbers>,
+    /// the techniques the solve of the best puzzle used, simplest first
+    pub used: Vec<&'static str>,
+    /// the required techniques the solve of the best puzzle didn't use
+    pub missing: Vec<&'static str>,
+    /// with [`Generator::set_minimal`], whether the best puzzle needs all the required
+    /// techniques but keeps a given that isn't needed
+    pub not_minimal: bool,
+    pub attempts: usize,
+    pub elapsed: Duration,
+}
+
+impl ProfileSearch {
+    /// the puzzle, if it matches the profile
+    pub fn found(&self) -> Option<Numbers> {
+        self.best
+            .filter(|_| self.missing.is_empty() && !self.not_minimal)
+    }
+}
+
+/// creates new puzzles with a unique solution
+pub struct Generator {
+    rng: StdRng,
+    minimal: bool,
+}
+
+impl Default for Generator {
+    fn default() -> Self {
+        Self {
+            rng: StdRng::from_entropy(),
+            minimal: false,
+        }
+    }
+}
+
+impl Generator {
+    /// a generator that always produces the same sequence of puzzles for the same seed
+    pub fn from_seed(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            minimal: false,
+        }
+    }
+
+    /// only generate [minimal](minimal::is_minimal) puzzles. without a difficulty they always
+    /// are, with one a given may be kept to keep the puzzle easy, and those are retried.
+    pub fn set_minimal(&mut self, minimal: bool) {
+        self.minimal = minimal;
+    }
+
+    /// generates a puzzle, retrying until it matches the requested difficulty, if any.
+    ///
+    /// starts from a random solved grid and removes givens in random order, as long as the
+    /// solution stays unique and the puzzle does not get harder than requested.
+    pub fn generate(&mut self, difficulty: Option<Difficulty>) -> Numbers {
+        loop {
+            let max_difficulty = difficulty.unwrap_or(Difficulty::Unknown);
+            let puzzle = self.generate_once(|candidate| {
+                max_difficulty == Difficulty::Unknown || rating::rate(candidate) <= max_difficulty
+            });
+            if difficulty.is_none_or(|d| rating::rate(&puzzle) == d)
+                && (!self.minimal || minimal::is_minimal(&puzzle))
+            {
+                return puzzle;
+            }
+        }
+    }
+
+    /// generates puzzles until one needs all the techniques of the profile and nothing harder
+    /// than its maximum, or the budget runs out. with [`set_minimal`](Self::set_minimal), only
+    /// minimal puzzles count as found.
+    pub fn generate_profile(&mut self, profile: &Profile, budget: Budget) -> ProfileSearch {
+        let techniques = rating::techniques();
+        let names = |indexes: &[usize]| -> Vec<&'static str> {
+            indexes
+                .iter()
+                .map(|&idx| techniques[idx].0.name())
+                .collect()
+        };
+
+        let start = Instant::now();
+        let mut search = ProfileSearch {
+            best: None,
+            used: vec![],
+            missing: vec![],
+            not_minimal: false,
+            attempts: 0,
+            elapsed: Duration::ZERO,
+        };
+        while budget
+            .attempts
+            .is_none_or(|attempts| search.attempts < attempts)
+            && budget.time.is_none_or(|time| start.elapsed() < time)
+        {
+            search.attempts += 1;
+            let puzzle = self.generate_once(|candidate| profile.used(candidate).is_some());
+            let used = profile
+                .used(&puzzle)
+                .expect("only solvable givens are removed");
+            let missing: Vec<usize> = profile
+                .required
+                .iter()
+                .copied()
+                .filter(|idx| !used.contains(idx))
+                .collect();
+            // close, but not what was asked for
+            let not_minimal = self.minimal && missing.is_empty() && !minimal::is_minimal(&puzzle);
+
+            if search.best.is_none()
+                || (missing.len(), not_minimal) < (search.missing.len(), search.not_minimal)
+            {
+                search.best = Some(puzzle);
+                search.used = names(&used);
+                search.missing = names(&missing);
+                search.not_minimal = not_minimal;
+            }
+            if search.found().is_some() {
+                break;
+            }
+        }
+
+        search.elapsed = start.elapsed();
+        search
+    }
+
+    /// starts from a random solved grid and removes givens in random order, as long as the
+    /// solution stays unique and `keep` accepts the puzzle without them
+    fn generate_once(&mut self, mut keep: impl FnMut(&Numbers) -> bool) -> Numbers {
+        let mut cells: [u8; 81] = brute_force::random_solution(&mut self.rng).into();
+
+        let mut order: Vec<usize> = (0..81).collect();
+        order.shuffle(&mut self.rng);
+
+        for idx in order {
+            let value = cells[idx];
+            cells[idx] = 0;
+
+            let candidate = Numbers::from(cells);
+            if !matches!(brute_force::solve(&candidate), Solutions::Unique(_)) || !keep(&candidate)
+            {
+                cells[idx] = value;
+            }
+        }
+
+        Numbers::from(cells)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn generates_unique_puzzles_of_requested_difficulty() {
+        let mut generator = Generator::from_seed(1);
+        for difficulty in [Difficulty::Easy, Difficulty::Medium] {
+            let puzzle = generator.generate(Some(difficulty));
+            assert!(matches!(brute_force::solve(&puzzle), Solutions::Unique(_)));
+            assert_eq!(difficulty, rating::rate(&puzzle));
+        }
+    }
+
+    #[test]
+    fn same_seed_generates_same_puzzle() {
+        let a = Generator::from_seed(42).generate(None);
+        let b = Generator::from_seed(42).generate(None);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn generates_minimal_puzzles() {
+        let mut generator = Generator::from_seed(5);
+        generator.set_minimal(true);
+        for difficulty in [None, Some(Difficulty::Medium)] {
+            assert!(minimal::is_minimal(&generator.generate(difficulty)));
+        }
+    }
+
+    #[test]
+    fn generates_puzzles_to_a_technique_profile() {
+        let profile = Profile::new(&["pointing"], Some("Claiming")).unwrap();
+        let budget = Budget {
+            attempts: Some(50),
+            time: None,
+        };
+        let search = Generator::from_seed(2).generate_profile(&profile, budget);
+        let puzzle = search.found().unwrap();
+        assert!(search.used.contains(&"Pointing"));
+        assert!(search.used.iter().all(|name| [
+            "Naked Single",
+            "Hidden Single",
+            "Pointing",
+            "Claiming"
+        ]
+        .contains(name)));
+        assert!(matches!(brute_force::solve(&puzzle), Solutions::Unique(_)));
+    }
+
+    #[test]
+    fn gives_up_when_the_budget_runs_out() {
+        let profile = Profile::new(&["Sue de Coq", "Jellyfish"], None).unwrap();
+        let budget = Budget {
+            attempts: Some(2),
+            time: None,
+        };
+        let search = Generator::from_seed(2).generate_profile(&profile, budget);
+        assert_eq!(2, search.attempts);
+        assert!(search.best.is_some());
+        assert!(!search.missing.is_empty());
+        assert_eq!(None, search.found());
+    }
+
+    #[test]
+    fn reports_puzzles_that_are_not_minimal() {
+        let profile = Profile::new(&["Hidden Single"], None).unwrap();
+        let budget = Budget {
+            attempts: Some(1),
+            time: None,
+        };
+        let mut generator = Generator::from_seed(2);
+        generator.set_minimal(true);
+        let search = generator.generate_profile(&profile, budget);
+        assert!(!minimal::is_minimal(&search.best.unwrap()));
+        assert!(search.missing.is_empty());
+        assert!(search.not_minimal);
+        assert_eq!(None, search.found());
+    }
+
+    #[test]
+    fn rejects_invalid_profiles() {
+        assert!(Profile::new(&["Swordfish"], Some("X-Wing")).is_err());
+        assert!(Profile::new(&["Nonsense"], None).is_err());
+        assert!(Profile::new(&[], None).is_err());
+    }
+}
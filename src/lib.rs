@@ -0,0 +1,17 @@
+pub mod batch;
+pub mod bitboard;
+pub mod brute_force;
+pub mod formats;
+pub mod generator;
+pub mod minimal;
+#[cfg(feature = "ocr")]
+pub mod ocr;
+pub mod rating;
+pub mod render;
+pub mod se_rating;
+#[cfg(feature = "serde")]
+pub mod serialization;
+pub mod solver;
+pub mod sudoku;
+pub mod symmetry;
+pub mod techniques;
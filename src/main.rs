@@ -1,12 +1,17 @@
 use std::num::NonZeroU8;
 
-use crate::solver::{GenerateBasicMarkingsStep, NakedSingleStep};
-use log::info;
+use ::sudoku::bitboard::Bitboard;
+use ::sudoku::formats::Format;
+#[cfg(feature = "ocr")]
+use ::sudoku::ocr;
+use ::sudoku::rating;
+use ::sudoku::solver::{self, GenerateBasicMarkingsStep, NakedSingleStep};
+use ::sudoku::sudoku;
+use log::{info, warn};
+use web_sys::{HtmlInputElement, HtmlTextAreaElement};
+use yew::platform::spawn_local;
 use yew::prelude::*;
 
-mod solver;
-mod sudoku;
-
 #[rustfmt::skip]
 static TEST_FIELD: [u8; 81] = [
     1, 0, 0,  0, 6, 0,  0, 0, 0,
@@ -36,9 +41,9 @@ fn Game() -> Html {
     let on_solver_step = {
         let game = game.clone();
         let solver = solver.clone();
-        Callback::from(move |_| {
-            let cgame = (*solver).borrow_mut().next_step(*game);
-            game.set(cgame);
+        Callback::from(move |_| match (*solver).borrow_mut().next_step(*game) {
+            Ok(cgame) => game.set(cgame),
+            Err(e) => warn!("solver step: {}", e),
         })
     };
 
@@ -59,10 +64,158 @@ fn Game() -> Html {
         )
     };
 
+    let on_open_file = {
+        let game = game.clone();
+        Callback::from(move |event: Event| {
+            let input: HtmlInputElement = event.target_unchecked_into();
+            let Some(file) = input.files().and_then(|files| files.get(0)) else {
+                return;
+            };
+
+            let file = gloo_file::File::from(file);
+            let format = file
+                .name()
+                .rsplit_once('.')
+                .and_then(|(_, extension)| Format::from_extension(extension))
+                .unwrap_or(Format::Line);
+
+            let game = game.clone();
+            spawn_local(async move {
+                match gloo_file::futures::read_as_text(&file).await {
+                    Ok(content) => match format.read(&content) {
+                        Ok(loaded) => game.set(loaded),
+                        Err(e) => warn!("could not read {}: {}", file.name(), e),
+                    },
+                    Err(e) => warn!("could not load {}: {}", file.name(), e),
+                }
+            });
+        })
+    };
+
+    let on_paste = {
+        let game = game.clone();
+        Callback::from(move |event: Event| {
+            let input: HtmlTextAreaElement = event.target_unchecked_into();
+            let content = input.value();
+            match Format::guess(&content).read(&content) {
+                Ok(loaded) => game.set(loaded),
+                Err(e) => warn!("could not read the pasted puzzle: {}", e),
+            }
+        })
+    };
+
+    #[cfg(feature = "ocr")]
+    let photo_scan = {
+        let game = game.clone();
+        let on_accept = Callback::from(move |scanned| game.set(scanned));
+        html! { <PhotoScan {on_accept} /> }
+    };
+    #[cfg(not(feature = "ocr"))]
+    let photo_scan = html! {};
+
+    // the solve path of the game when the log was opened, and the entry the board shows
+    let log = use_state_eq(|| None::<(solver::SolveLog, usize)>);
+
+    let on_solve_log = {
+        let game = game.clone();
+        let log = log.clone();
+        Callback::from(move |_| {
+            let mut solver = solver::Solver::default();
+            for (step, _) in rating::techniques() {
+                solver.add_step(step);
+            }
+            log.set(Some((solver.solve_log(*game), 0)));
+        })
+    };
+
+    // the cells of the chain or pattern behind the shown step
+    let step_cells = match &*log {
+        Some((solve_log, shown)) if *shown > 0 => {
+            let entry = &solve_log.entries[shown - 1];
+            entry
+                .chain
+                .iter()
+                .chain(&entry.branches)
+                .flat_map(|chain| &chain.nodes)
+                .map(|node| &node.cells)
+                .chain(entry.pattern.iter().map(|part| &part.cells))
+                .flatten()
+                .fold(Bitboard::EMPTY, |cells, &cell| cells | Bitboard::cell(cell))
+        }
+        _ => Bitboard::EMPTY,
+    };
+
+    // the color classes of a coloring step, "color 1" to "color 4"
+    let mut step_colors = [Bitboard::EMPTY; 4];
+    if let Some((solve_log, shown)) = &*log {
+        if *shown > 0 {
+            for part in &solve_log.entries[shown - 1].pattern {
+                let color = part
+                    .role
+                    .strip_prefix("color ")
+                    .and_then(|n| n.parse::<usize>().ok());
+                if let Some(color @ 1..=4) = color {
+                    step_colors[color - 1] = part
+                        .cells
+                        .iter()
+                        .fold(Bitboard::EMPTY, |cells, &cell| cells | Bitboard::cell(cell));
+                }
+            }
+        }
+    }
+
+    let log_view = match &*log {
+        Some((solve_log, shown)) => {
+            let replay = |steps: usize| {
+                let game = game.clone();
+                let log = log.clone();
+                let solve_log = solve_log.clone();
+                Callback::from(move |_| {
+                    game.set(solve_log.game_at(steps));
+                    log.set(Some((solve_log.clone(), steps)));
+                })
+            };
+
+            html! {
+                <ol class="solve-log" start="0">
+                    <li class={classes!((*shown == 0).then_some("selected"))} onclick={replay(0)}>
+                        { "start" }
+                    </li>
+                    {
+                        solve_log.entries.iter().enumerate().map(|(i, entry)| html! {
+                            <li class={classes!((*shown == i + 1).then_some("selected"))} onclick={replay(i + 1)}>
+                                { &entry.explanation }
+                            </li>
+                        }).collect::<Html>()
+                    }
+                    {
+                        if solve_log.is_solved() {
+                            html! {}
+                        } else {
+                            html! { <li>{ "stuck, no technique makes progress" }</li> }
+                        }
+                    }
+                </ol>
+            }
+        }
+        None => html! {},
+    };
+
     html! {
         <div>
             <button onclick={on_solver_step}>{ "run solver step" }</button>
-            <Field game={*game} number_input={on_number_input} />
+            <button onclick={on_solve_log}>{ "show solve log" }</button>
+            <label>
+                { "open file " }
+                <input type="file" accept=".sdk,.ss,.txt" onchange={on_open_file} />
+            </label>
+            <label>
+                { "paste puzzle " }
+                <textarea rows="3" onchange={on_paste} />
+            </label>
+            { photo_scan }
+            <Field game={*game} number_input={on_number_input} highlighted={step_cells} colors={step_colors} />
+            { log_view }
             <div>
                 {
                     if game.is_valid() {
@@ -76,16 +229,114 @@ fn Game() -> Html {
     }
 }
 
+#[cfg(feature = "ocr")]
+#[derive(PartialEq, Properties)]
+struct PhotoScanProps {
+    on_accept: Callback<sudoku::Game, ()>,
+}
+
+#[cfg(feature = "ocr")]
+#[function_component]
+fn PhotoScan(props: &PhotoScanProps) -> Html {
+    // a puzzle read from a photo, with the cells the player should check, until it is accepted
+    let scanned = use_state_eq(|| None::<(sudoku::Game, Bitboard)>);
+
+    let on_open_photo = {
+        let scanned = scanned.clone();
+        Callback::from(move |event: Event| {
+            let input: HtmlInputElement = event.target_unchecked_into();
+            let Some(file) = input.files().and_then(|files| files.get(0)) else {
+                return;
+            };
+
+            let file = gloo_file::File::from(file);
+            let scanned = scanned.clone();
+            spawn_local(async move {
+                match gloo_file::futures::read_as_bytes(&file).await {
+                    Ok(bytes) => match ocr::recognize_bytes(&bytes) {
+                        Ok(recognition) => {
+                            // the digits are entries rather than givens, so misread ones can be fixed
+                            let mut game = sudoku::Game::create([0; 81]);
+                            for (idx, value) in recognition.numbers.cells().enumerate() {
+                                let (row, col) = sudoku::Game::cell_index_to_coords(idx);
+                                game.set(row, col, value);
+                            }
+                            scanned.set(Some((game, recognition.uncertain())));
+                        }
+                        Err(e) => warn!("could not read {}: {}", file.name(), e),
+                    },
+                    Err(e) => warn!("could not load {}: {}", file.name(), e),
+                }
+            });
+        })
+    };
+
+    let on_scan_input = {
+        let scanned = scanned.clone();
+        Callback::from(move |(row, col, value, _): (usize, usize, u8, bool)| {
+            if let Some((mut cgame, mut uncertain)) = *scanned {
+                cgame.set(row, col, value);
+                uncertain.remove(sudoku::Game::coords_to_cell_index(row, col));
+                scanned.set(Some((cgame, uncertain)));
+            }
+        })
+    };
+
+    let on_accept_scan = {
+        let on_accept = props.on_accept.clone();
+        let scanned = scanned.clone();
+        Callback::from(move |_| {
+            if let Some((cgame, _)) = *scanned {
+                on_accept.emit(sudoku::Game::create(cgame.current_numbers()));
+                scanned.set(None);
+            }
+        })
+    };
+
+    let on_discard_scan = {
+        let scanned = scanned.clone();
+        Callback::from(move |_| scanned.set(None))
+    };
+
+    html! {
+        <div>
+            <label>
+                { "scan photo " }
+                <input type="file" accept="image/png,image/jpeg" onchange={on_open_photo} />
+            </label>
+            {
+                if let Some((scanned_game, uncertain)) = *scanned {
+                    html! {
+                        <div>
+                            <div>{ "check the scanned puzzle, highlighted cells may be misread" }</div>
+                            <Field game={scanned_game} number_input={on_scan_input} highlighted={uncertain} />
+                            <button onclick={on_accept_scan}>{ "use puzzle" }</button>
+                            <button onclick={on_discard_scan}>{ "discard" }</button>
+                        </div>
+                    }
+                } else {
+                    html! {}
+                }
+            }
+        </div>
+    }
+}
+
 #[derive(PartialEq, Properties)]
 struct FieldProps {
     game: sudoku::Game,
     number_input: Callback<(usize, usize, u8, bool), ()>,
+    #[prop_or_default]
+    highlighted: Bitboard,
+    #[prop_or_default]
+    colors: [Bitboard; 4],
 }
 
 #[function_component]
 fn Field(props: &FieldProps) -> Html {
     let selected = use_state_eq(|| None);
 
+    #[allow(unused_variables, clippy::needless_return)]
     let create_keyboard_input =
         |row: usize,
          col: usize,
@@ -165,7 +416,7 @@ fn Field(props: &FieldProps) -> Html {
                     };
 
                     html! {
-                        <Cell idx={idx as u8} onkeyup={onkeyup} onfocus={on_cell_select} selected={ Some(idx) == *selected } fixed={fixed} value={value} markings={markings} />
+                        <Cell idx={idx as u8} onkeyup={onkeyup} onfocus={on_cell_select} selected={ Some(idx) == *selected } fixed={fixed} highlighted={props.highlighted.contains(game_index)} color={props.colors.iter().position(|cells| cells.contains(game_index)).map(|color| color + 1)} value={value} markings={markings} />
                     }
                 })
                 .collect::<Html>()
@@ -187,6 +438,10 @@ pub struct CellProps {
     #[prop_or_default]
     fixed: bool,
     #[prop_or_default]
+    highlighted: bool,
+    #[prop_or_default]
+    color: Option<usize>,
+    #[prop_or_default]
     markings: Option<[bool; 9]>,
 }
 
@@ -218,7 +473,7 @@ fn Cell(props: &CellProps) -> Html {
         <div tabindex={idx}
             onfocus={props.onfocus.clone()}
             onkeyup={props.onkeyup.clone()}
-            class={classes!("cell", props.selected.then_some("selected"), props.fixed.then_some("fixed"))}
+            class={classes!("cell", props.selected.then_some("selected"), props.fixed.then_some("fixed"), props.highlighted.then_some("highlighted"), props.color.map(|color| format!("color-{color}")))}
             >
             { content }
         </div>
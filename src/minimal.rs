@@ -0,0 +1,134 @@
+//! minimal puzzles: a puzzle is minimal when every given is needed, removing any one of them
+//! leaves more than one solution.
+
+use crate::brute_force::{self, Solutions};
+use crate::sudoku::Numbers;
+use crate::symmetry::Symmetry;
+
+fn is_unique(numbers: &Numbers) -> bool {
+    matches!(brute_force::solve(numbers), Solutions::Unique(_))
+}
+
+/// the givens, as cell indexes, that can be removed on their own without losing the unique
+/// solution. empty if the puzzle has no unique solution to begin with.
+pub fn redundant_givens(numbers: &Numbers) -> Vec<usize> {
+    if !is_unique(numbers) {
+        return vec![];
+    }
+
+    let mut cells: [u8; 81] = (*numbers).into();
+    (0..81)
+        .filter(|&idx| {
+            let value = cells[idx];
+            if value == 0 {
+                return false;
+            }
+
+            cells[idx] = 0;
+            let redundant = is_unique(&Numbers::from(cells));
+            cells[idx] = value;
+            redundant
+        })
+        .collect()
+}
+
+/// whether the puzzle has a unique solution and needs every one of its givens for it
+pub fn is_minimal(numbers: &Numbers) -> bool {
+    is_unique(numbers) && redundant_givens(numbers).is_empty()
+}
+
+/// removes givens in cell order as long as the solution stays unique, or returns `None` if it
+/// isn't unique to begin with.
+///
+/// with a symmetry, a given and its partner are only removed together, so a symmetric pattern
+/// stays symmetric. the result is then minimal among the symmetric puzzles, but one of the
+/// givens on its own may still be redundant.
+pub fn minimize(numbers: &Numbers, symmetry: Symmetry) -> Option<Numbers> {
+    if !is_unique(numbers) {
+        return None;
+    }
+
+    let mut cells: [u8; 81] = (*numbers).into();
+    for idx in 0..81 {
+        let partner = symmetry.partner(idx);
+        if partner < idx || cells[idx] == 0 && cells[partner] == 0 {
+            continue;
+        }
+
+        let kept = (cells[idx], cells[partner]);
+        cells[idx] = 0;
+        cells[partner] = 0;
+        if !is_unique(&Numbers::from(cells)) {
+            (cells[idx], cells[partner]) = kept;
+        }
+    }
+
+    Some(Numbers::from(cells))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PUZZLE: &str =
+        "1...6....98....6.5.....5..1......3.4.6.13.9...4.72.....93.761....648...75..9.246.";
+
+    /// the puzzle with two more givens from its solution
+    fn with_extra_givens() -> (Numbers, Vec<usize>) {
+        let puzzle: Numbers = PUZZLE.parse().unwrap();
+        let Solutions::Unique(solution) = brute_force::solve(&puzzle) else {
+            panic!("the puzzle has a unique solution");
+        };
+
+        let mut cells: [u8; 81] = puzzle.into();
+        let extra = vec![1, 80];
+        for &idx in &extra {
+            cells[idx] = solution.get_by_offset(idx);
+        }
+        (Numbers::from(cells), extra)
+    }
+
+    #[test]
+    fn minimized_puzzles_are_minimal_with_the_same_solution() {
+        let (puzzle, extra) = with_extra_givens();
+        assert!(!is_minimal(&puzzle));
+        assert!(extra
+            .iter()
+            .all(|idx| redundant_givens(&puzzle).contains(idx)));
+
+        let minimal = minimize(&puzzle, Symmetry::None).unwrap();
+        assert!(is_minimal(&minimal));
+        assert!(redundant_givens(&minimal).is_empty());
+        assert_eq!(brute_force::solve(&puzzle), brute_force::solve(&minimal));
+        assert_eq!(minimal, minimize(&minimal, Symmetry::None).unwrap());
+    }
+
+    #[test]
+    fn minimizing_keeps_symmetry() {
+        let (puzzle, _) = with_extra_givens();
+        let mut cells: [u8; 81] = puzzle.into();
+        let Solutions::Unique(solution) = brute_force::solve(&puzzle) else {
+            panic!("the puzzle has a unique solution");
+        };
+        // fill in the partners of the givens, so the pattern is symmetric
+        for idx in 0..81 {
+            if cells[idx] != 0 {
+                cells[80 - idx] = solution.get_by_offset(80 - idx);
+            }
+        }
+
+        let symmetric = Numbers::from(cells);
+        assert!(Symmetry::Rotational.is_kept_by(&symmetric));
+        let minimal = minimize(&symmetric, Symmetry::Rotational).unwrap();
+        assert!(Symmetry::Rotational.is_kept_by(&minimal));
+        assert!(matches!(brute_force::solve(&minimal), Solutions::Unique(_)));
+    }
+
+    #[test]
+    fn puzzles_without_a_unique_solution_are_not_minimal() {
+        let empty = Numbers::empty();
+        assert!(!is_minimal(&empty));
+        assert!(redundant_givens(&empty).is_empty());
+        assert_eq!(None, minimize(&empty, Symmetry::None));
+    }
+}
@@ -0,0 +1,590 @@
+//! reading puzzles from photos of printed grids, offline and without any outside service,
+//! enabled with the `ocr` feature.
+//!
+//! the pipeline has four stages:
+//!
+//! 1. the photo is scaled down and binarized with an adaptive threshold, so uneven lighting
+//!    doesn't matter
+//! 2. the largest connected blob of ink is taken as the grid, and its four outermost points as
+//!    the corners
+//! 3. a homography from the corners to a square undoes the perspective, and the straightened
+//!    grid is cut into 81 cells
+//! 4. the ink in each cell is compared against embedded digit templates of a few typefaces, and
+//!    the digit with the closest template wins
+//!
+//! every cell comes with a confidence, so a UI can ask the player to confirm the cells where
+//! the classifier wasn't sure.
+
+use std::collections::VecDeque;
+use std::fmt;
+
+use image::GrayImage;
+
+use crate::bitboard::Bitboard;
+use crate::sudoku::Numbers;
+
+/// photos are scaled down to this size of their longer side before looking for the grid
+const WORKING_SIZE: u32 = 800;
+/// the side of a cell in the straightened grid, in pixels
+const CELL: usize = 40;
+/// the part of a cell on each side that is ignored, so the grid lines don't count as ink
+const CELL_MARGIN: usize = CELL / 6;
+/// the columns and rows of the grid a digit is resampled to before comparing it
+const FEATURE_SIZE: (usize, usize) = (8, 10);
+/// cells below this confidence should be confirmed by the player
+pub const UNCERTAIN: f32 = 0.15;
+
+/// the digit templates, nine bitmaps for each typeface: a 5x7 pixel font, DejaVu Sans and
+/// DejaVu Serif
+#[rustfmt::skip]
+const TYPEFACES: [[&[&str]; 9]; 3] = [
+    [
+        &["..#..", ".##..", "..#..", "..#..", "..#..", "..#..", ".###."],
+        &[".###.", "#...#", "....#", "...#.", "..#..", ".#...", "#####"],
+        &["#####", "...#.", "..#..", "...#.", "....#", "#...#", ".###."],
+        &["...#.", "..##.", ".#.#.", "#..#.", "#####", "...#.", "...#."],
+        &["#####", "#....", "####.", "....#", "....#", "#...#", ".###."],
+        &["..##.", ".#...", "#....", "####.", "#...#", "#...#", ".###."],
+        &["#####", "....#", "...#.", "..#..", ".#...", ".#...", ".#..."],
+        &[".###.", "#...#", "#...#", ".###.", "#...#", "#...#", ".###."],
+        &[".###.", "#...#", "#...#", ".####", "....#", "...#.", ".##.."],
+    ],
+    [
+        &[".####...", "#####...", "...##...", "...##...", "...##...", "...##...", "...##...", "...##...", "...##...", "...##...", "...##...", "...##...", "########", "########"],
+        &[".######..", "########.", "#.....###", ".......##", ".......##", "......###", "......##.", ".....##..", "....##...", "...##....", "..##.....", ".##......", "#########", "#########"],
+        &[".######..", "########.", "......###", ".......##", ".......##", "......##.", "..#####..", ".....###.", ".......##", ".......##", ".......##", "......###", "########.", "#######.."],
+        &["......##..", ".....###..", "....####..", "....#.##..", "...##.##..", "..##..##..", "..#...##..", ".##...##..", "##....##..", "##########", "##########", "......##..", "......##..", "......##.."],
+        &[".#######.", ".#######.", ".#.......", ".#.......", ".##......", ".######..", ".#...###.", "......###", ".......##", ".......##", ".......##", "......###", "########.", "#######.."],
+        &["...#####.", "..#######", ".##......", ".##......", "##.......", "##.#####.", "####..###", "###....##", "##.....##", "##.....##", "##.....##", ".##....##", ".###.####", "..#####.."],
+        &["#########", "#########", "......###", "......##.", "......##.", ".....##..", ".....##..", "....###..", "....##...", "....##...", "...##....", "...##....", "...##....", "..##....."],
+        &["..#####..", ".#######.", "###....##", "##.....##", "##.....##", ".##...##.", "..#####..", ".#######.", "##.....##", "##.....##", "##.....##", "##.....##", ".###.####", "..######."],
+        &["..#####..", ".#######.", "###....##", "##.....##", "##.....##", "##.....##", "##.....##", ".###..###", "..#######", ".......##", ".......##", "......##.", ".#######.", ".#####..."],
+    ],
+    [
+        &["...##..", ".####..", "##.##..", "...##..", "...##..", "...##..", "...##..", "...##..", "...##..", "...##..", "...##..", "...##..", "...##..", "#######"],
+        &[".######..", "##....##.", "##....###", "#......##", ".......##", "......##.", "......##.", ".....##..", "....##...", "...##....", "..##.....", ".##.....#", "#########", "#########"],
+        &[".######..", "###...##.", "##....##.", ".......##", "......##.", "......##.", "...####..", "......##.", ".......##", ".......##", "#......##", "#......##", "##....##.", ".######.."],
+        &["......##...", ".....###...", "....####...", "....#.##...", "...##.##...", "...#..##...", "..#...##...", ".##...##...", "##....##...", "###########", "......##...", "......##...", "......##...", "....######."],
+        &["########.", "########.", "##.......", "##.......", "##.......", "#######..", "##....##.", ".......##", ".......##", ".......##", "#......##", "#......##", "##....##.", ".######.."],
+        &["...######", "..##...##", ".##.....#", ".#.......", "##.......", "##.#####.", "###...###", "###....##", "##.....##", "##.....##", "##.....##", ".##....##", ".##...###", "..#####.."],
+        &["#########", "#########", "#......##", ".......#.", "......##.", "......##.", "......#..", ".....##..", ".....#...", "....##...", "....#....", "...##....", "...##....", "...#....."],
+        &["..#####..", ".##...##.", "###....##", "##.....##", "###....##", ".##...##.", "..#####..", ".##...##.", "##.....##", "##.....##", "##.....##", "##.....##", ".##...###", "..######."],
+        &["..#####..", ".##...##.", "##.....##", "##.....##", "##.....##", "##.....##", "##.....##", ".##...###", "..#######", ".......##", ".......##", ".#.....##", ".#....##.", ".######.."],
+    ],
+];
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum OcrError {
+    /// the bytes are not an image in a supported format
+    Decode(String),
+    /// nothing in the image looks like a grid
+    NoGrid,
+}
+
+impl fmt::Display for OcrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OcrError::Decode(e) => write!(f, "could not decode the image: {}", e),
+            OcrError::NoGrid => f.write_str("could not find a grid in the image"),
+        }
+    }
+}
+
+impl std::error::Error for OcrError {}
+
+/// the digits read from a photo
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Recognition {
+    pub numbers: Numbers,
+    /// how sure the classifier is about each cell, from 0 to 1. empty cells have 1.
+    pub confidence: [f32; 81],
+}
+
+impl Recognition {
+    /// the cells the player should confirm
+    pub fn uncertain(&self) -> Bitboard {
+        let mut cells = Bitboard::EMPTY;
+        for (idx, &confidence) in self.confidence.iter().enumerate() {
+            if confidence < UNCERTAIN {
+                cells.insert(idx);
+            }
+        }
+
+        cells
+    }
+}
+
+/// decodes a PNG or JPEG image and reads the puzzle in it
+pub fn recognize_bytes(bytes: &[u8]) -> Result<Recognition, OcrError> {
+    let image = image::load_from_memory(bytes).map_err(|e| OcrError::Decode(e.to_string()))?;
+    recognize(&image.to_luma8())
+}
+
+/// reads the puzzle in a grayscale photo
+pub fn recognize(image: &GrayImage) -> Result<Recognition, OcrError> {
+    let image = if image.width().max(image.height()) > WORKING_SIZE {
+        let scale = WORKING_SIZE as f64 / image.width().max(image.height()) as f64;
+        image::imageops::resize(
+            image,
+            ((image.width() as f64 * scale) as u32).max(1),
+            ((image.height() as f64 * scale) as u32).max(1),
+            image::imageops::FilterType::Triangle,
+        )
+    } else {
+        image.clone()
+    };
+    // too small to hold nine cells a side, also after a very narrow photo was scaled down
+    if image.width() < 9 || image.height() < 9 {
+        return Err(OcrError::NoGrid);
+    }
+
+    let corners = find_grid(&image).ok_or(OcrError::NoGrid)?;
+    let size = (CELL * 9) as f64;
+    let square = [(0.0, 0.0), (size, 0.0), (size, size), (0.0, size)];
+    let straightened = warp(&image, &Homography::solve(square, corners), CELL * 9);
+
+    // the templates of every typeface, with the digit they show
+    let templates: Vec<([f32; FEATURE_SIZE.0 * FEATURE_SIZE.1], u8)> = TYPEFACES
+        .iter()
+        .flat_map(|glyphs| glyphs.iter().zip(1..))
+        .map(|(glyph, digit)| {
+            let ink = |x: usize, y: usize| glyph[y].as_bytes()[x] == b'#';
+            let bounds = bounds(
+                (0..glyph.len())
+                    .flat_map(|y| (0..glyph[y].len()).map(move |x| (x, y)))
+                    .filter(|&(x, y)| ink(x, y)),
+            );
+            (features(ink, bounds), digit)
+        })
+        .collect();
+
+    let mut numbers = [0u8; 81];
+    let mut confidence = [1.0; 81];
+    for idx in 0..81 {
+        if let Some(features) = cell_features(&straightened, idx) {
+            // the best score of each digit over all typefaces
+            let mut scores = [(f32::MIN, 0); 9];
+            for (template, digit) in &templates {
+                let score = &mut scores[*digit as usize - 1];
+                *score = (score.0.max(correlation(&features, template)), *digit);
+            }
+            scores.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+            numbers[idx] = scores[0].1;
+            confidence[idx] = (scores[0].0 - scores[1].0).clamp(0.0, 1.0);
+        }
+    }
+
+    Ok(Recognition {
+        numbers: numbers.into(),
+        confidence,
+    })
+}
+
+/// marks dark pixels that are darker than their surroundings
+fn binarize(image: &GrayImage, radius: usize) -> Vec<bool> {
+    let (width, height) = (image.width() as usize, image.height() as usize);
+
+    // summed area table with an extra row and column of zeros
+    let mut sums = vec![0u64; (width + 1) * (height + 1)];
+    for y in 0..height {
+        let mut row = 0u64;
+        for x in 0..width {
+            row += image.get_pixel(x as u32, y as u32).0[0] as u64;
+            sums[(y + 1) * (width + 1) + x + 1] = sums[y * (width + 1) + x + 1] + row;
+        }
+    }
+
+    let mut ink = vec![false; width * height];
+    for y in 0..height {
+        for x in 0..width {
+            let (x0, y0) = (x.saturating_sub(radius), y.saturating_sub(radius));
+            let (x1, y1) = ((x + radius + 1).min(width), (y + radius + 1).min(height));
+            let sum = sums[y1 * (width + 1) + x1] + sums[y0 * (width + 1) + x0]
+                - sums[y0 * (width + 1) + x1]
+                - sums[y1 * (width + 1) + x0];
+            let mean = sum as f64 / ((x1 - x0) * (y1 - y0)) as f64;
+            ink[y * width + x] = (image.get_pixel(x as u32, y as u32).0[0] as f64) < mean - 15.0;
+        }
+    }
+
+    ink
+}
+
+/// the 8-connected blobs of ink, as lists of pixel offsets
+fn components(ink: &[bool], width: usize) -> Vec<Vec<usize>> {
+    let height = ink.len() / width;
+    let mut seen = vec![false; ink.len()];
+    let mut components = vec![];
+
+    for start in 0..ink.len() {
+        if !ink[start] || seen[start] {
+            continue;
+        }
+
+        let mut component = vec![];
+        let mut queue = VecDeque::from([start]);
+        seen[start] = true;
+        while let Some(pixel) = queue.pop_front() {
+            component.push(pixel);
+            let (x, y) = (pixel % width, pixel / width);
+            for ny in y.saturating_sub(1)..(y + 2).min(height) {
+                for nx in x.saturating_sub(1)..(x + 2).min(width) {
+                    let next = ny * width + nx;
+                    if ink[next] && !seen[next] {
+                        seen[next] = true;
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        components.push(component);
+    }
+
+    components
+}
+
+/// the bounding box of some points as `(x0, y0, x1, y1)`, with the upper bounds exclusive
+fn bounds(points: impl Iterator<Item = (usize, usize)>) -> (usize, usize, usize, usize) {
+    points.fold(
+        (usize::MAX, usize::MAX, 0, 0),
+        |(x0, y0, x1, y1), (x, y)| (x0.min(x), y0.min(y), x1.max(x + 1), y1.max(y + 1)),
+    )
+}
+
+/// the top left, top right, bottom right and bottom left corner of the grid
+fn find_grid(image: &GrayImage) -> Option<[(f64, f64); 4]> {
+    let width = image.width() as usize;
+    let radius = (image.width().min(image.height()) as usize / 40).max(2);
+    let ink = binarize(image, radius);
+    let grid = components(&ink, width)
+        .into_iter()
+        .max_by_key(|component| component.len())?;
+
+    let points = || grid.iter().map(|&pixel| (pixel % width, pixel / width));
+    let (x0, y0, x1, y1) = bounds(points());
+    let min_side = image.width().min(image.height()) as usize / 4;
+    if x1 - x0 < min_side || y1 - y0 < min_side {
+        return None;
+    }
+
+    let corner = |key: fn((i64, i64)) -> i64| {
+        let (x, y) = points()
+            .max_by_key(|&(x, y)| key((x as i64, y as i64)))
+            .unwrap();
+        (x as f64 + 0.5, y as f64 + 0.5)
+    };
+    Some([
+        corner(|(x, y)| -x - y),
+        corner(|(x, y)| x - y),
+        corner(|(x, y)| x + y),
+        corner(|(x, y)| y - x),
+    ])
+}
+
+/// a projective transformation of the plane
+#[derive(Debug, Clone, Copy)]
+struct Homography([f64; 8]);
+
+impl Homography {
+    /// the transformation that maps each of the points `from` to the same point of `to`
+    fn solve(from: [(f64, f64); 4], to: [(f64, f64); 4]) -> Homography {
+        let mut system = [[0.0; 9]; 8];
+        for (i, (&(x, y), &(u, v))) in from.iter().zip(&to).enumerate() {
+            system[i * 2] = [x, y, 1.0, 0.0, 0.0, 0.0, -u * x, -u * y, u];
+            system[i * 2 + 1] = [0.0, 0.0, 0.0, x, y, 1.0, -v * x, -v * y, v];
+        }
+
+        // gaussian elimination with partial pivoting
+        for col in 0..8 {
+            let pivot = (col..8)
+                .max_by(|&a, &b| system[a][col].abs().total_cmp(&system[b][col].abs()))
+                .unwrap();
+            system.swap(col, pivot);
+            for row in 0..8 {
+                if row != col && system[col][col] != 0.0 {
+                    let pivot_row = system[col];
+                    let factor = system[row][col] / pivot_row[col];
+                    for (value, pivot) in system[row].iter_mut().zip(pivot_row).skip(col) {
+                        *value -= factor * pivot;
+                    }
+                }
+            }
+        }
+
+        let mut h = [0.0; 8];
+        for (i, h) in h.iter_mut().enumerate() {
+            *h = system[i][8] / system[i][i];
+        }
+        Homography(h)
+    }
+
+    fn map(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        let h = &self.0;
+        let w = h[6] * x + h[7] * y + 1.0;
+        (
+            (h[0] * x + h[1] * y + h[2]) / w,
+            (h[3] * x + h[4] * y + h[5]) / w,
+        )
+    }
+}
+
+/// samples a square image of `size` pixels, where `homography` maps its pixels into `image`
+fn warp(image: &GrayImage, homography: &Homography, size: usize) -> GrayImage {
+    let pixel = |x: i64, y: i64| {
+        let x = x.clamp(0, image.width() as i64 - 1) as u32;
+        let y = y.clamp(0, image.height() as i64 - 1) as u32;
+        image.get_pixel(x, y).0[0] as f64
+    };
+
+    GrayImage::from_fn(size as u32, size as u32, |x, y| {
+        let (sx, sy) = homography.map((x as f64 + 0.5, y as f64 + 0.5));
+        let (sx, sy) = (sx - 0.5, sy - 0.5);
+        let (x0, y0) = (sx.floor() as i64, sy.floor() as i64);
+        let (fx, fy) = (sx - sx.floor(), sy - sy.floor());
+
+        let value = pixel(x0, y0) * (1.0 - fx) * (1.0 - fy)
+            + pixel(x0 + 1, y0) * fx * (1.0 - fy)
+            + pixel(x0, y0 + 1) * (1.0 - fx) * fy
+            + pixel(x0 + 1, y0 + 1) * fx * fy;
+        image::Luma([value.round() as u8])
+    })
+}
+
+/// the features of the digit in a cell of the straightened grid, or `None` if it is empty
+fn cell_features(grid: &GrayImage, idx: usize) -> Option<[f32; FEATURE_SIZE.0 * FEATURE_SIZE.1]> {
+    let inner = CELL - CELL_MARGIN * 2;
+    let left = (idx % 9) * CELL + CELL_MARGIN;
+    let top = (idx / 9) * CELL + CELL_MARGIN;
+    let cell = image::imageops::crop_imm(grid, left as u32, top as u32, inner as u32, inner as u32)
+        .to_image();
+
+    // a large radius, so the threshold isn't thrown off by the ink of thick digits
+    let ink = binarize(&cell, inner / 2);
+    // a digit may fall apart into a few blobs, so everything but specks and thin slivers along
+    // the edges, which are what is left of the grid lines, counts
+    let digit: Vec<usize> = components(&ink, inner)
+        .into_iter()
+        .filter(|component| {
+            let (x0, y0, x1, y1) = bounds(component.iter().map(|&p| (p % inner, p / inner)));
+            let on_edge = x0 == 0 || y0 == 0 || x1 == inner || y1 == inner;
+            component.len() > 3 && !(on_edge && (x1 - x0 <= 3 || y1 - y0 <= 3))
+        })
+        .flatten()
+        .collect();
+
+    let (x0, y0, x1, y1) = bounds(digit.iter().map(|&p| (p % inner, p / inner)));
+    if digit.len() * 40 < inner * inner || (y1 - y0) * 3 < CELL {
+        return None;
+    }
+
+    let mut mask = vec![false; inner * inner];
+    for &pixel in &digit {
+        mask[pixel] = true;
+    }
+    Some(features(|x, y| mask[y * inner + x], (x0, y0, x1, y1)))
+}
+
+/// resamples the ink within `bounds` to the feature grid, as the share of ink in each of its
+/// cells. the bounds are widened to the aspect ratio of the feature grid first, so narrow
+/// digits like 1 keep their shape.
+fn features(
+    ink: impl Fn(usize, usize) -> bool,
+    (x0, y0, x1, y1): (usize, usize, usize, usize),
+) -> [f32; FEATURE_SIZE.0 * FEATURE_SIZE.1] {
+    const SAMPLES: usize = 4;
+    let (cols, rows) = FEATURE_SIZE;
+
+    let (mut width, mut height) = ((x1 - x0) as f64, (y1 - y0) as f64);
+    let aspect = cols as f64 / rows as f64;
+    if width < height * aspect {
+        width = height * aspect;
+    } else {
+        height = width / aspect;
+    }
+    let left = (x0 + x1) as f64 / 2.0 - width / 2.0;
+    let top = (y0 + y1) as f64 / 2.0 - height / 2.0;
+
+    let mut features = [0.0; FEATURE_SIZE.0 * FEATURE_SIZE.1];
+    for (i, feature) in features.iter_mut().enumerate() {
+        let (col, row) = (i % cols, i / cols);
+        let mut hits = 0;
+        for sy in 0..SAMPLES {
+            for sx in 0..SAMPLES {
+                let x =
+                    left + (col as f64 + (sx as f64 + 0.5) / SAMPLES as f64) * width / cols as f64;
+                let y =
+                    top + (row as f64 + (sy as f64 + 0.5) / SAMPLES as f64) * height / rows as f64;
+                if x >= x0 as f64
+                    && y >= y0 as f64
+                    && x < x1 as f64
+                    && y < y1 as f64
+                    && ink(x as usize, y as usize)
+                {
+                    hits += 1;
+                }
+            }
+        }
+        *feature = hits as f32 / (SAMPLES * SAMPLES) as f32;
+    }
+
+    features
+}
+
+/// the pearson correlation of two feature vectors, from -1 to 1
+fn correlation(a: &[f32], b: &[f32]) -> f32 {
+    let mean = |v: &[f32]| v.iter().sum::<f32>() / v.len() as f32;
+    let (mean_a, mean_b) = (mean(a), mean(b));
+
+    let (mut ab, mut aa, mut bb) = (0.0, 0.0, 0.0);
+    for (&a, &b) in a.iter().zip(b) {
+        ab += (a - mean_a) * (b - mean_b);
+        aa += (a - mean_a) * (a - mean_a);
+        bb += (b - mean_b) * (b - mean_b);
+    }
+
+    if aa == 0.0 || bb == 0.0 {
+        0.0
+    } else {
+        ab / (aa * bb).sqrt()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::path::Path;
+
+    use super::*;
+
+    const PUZZLE: &str =
+        "1...6....98....6.5.....5..1......3.4.6.13.9...4.72.....93.761....648...75..9.246.";
+
+    /// renders a puzzle as if photographed at an angle: the grid is mapped onto `corners`
+    /// of a `width` x `height` image, with some noise on top
+    fn photo(puzzle: &Numbers, width: u32, height: u32, corners: [(f64, f64); 4]) -> GrayImage {
+        let square = [(0.0, 0.0), (9.0, 0.0), (9.0, 9.0), (0.0, 9.0)];
+        let to_grid = Homography::solve(corners, square);
+        let mut noise = 12345u32;
+
+        GrayImage::from_fn(width, height, |x, y| {
+            let (gx, gy) = to_grid.map((x as f64 + 0.5, y as f64 + 0.5));
+            let inside = (0.0..9.0).contains(&gx) && (0.0..9.0).contains(&gy);
+
+            let line = |v: f64| {
+                let nearest = v.round();
+                let width = if nearest as i64 % 3 == 0 { 0.05 } else { 0.02 };
+                (v - nearest).abs() < width
+            };
+            let on_line = (-0.05..9.05).contains(&gx)
+                && (-0.05..9.05).contains(&gy)
+                && (line(gx) || line(gy));
+
+            let in_digit = inside && {
+                let (col, row) = (gx as usize, gy as usize);
+                let (fx, fy) = (gx.fract(), gy.fract());
+                let digit = puzzle.get_by_offset(row * 9 + col);
+                // a 5x7 glyph with pixels of 0.1 cells, centered in the cell
+                let (px, py) = ((fx - 0.25) / 0.1, (fy - 0.15) / 0.1);
+                digit != 0
+                    && (0.0..5.0).contains(&px)
+                    && (0.0..7.0).contains(&py)
+                    && TYPEFACES[0][digit as usize - 1][py as usize].as_bytes()[px as usize] == b'#'
+            };
+
+            noise = noise.wrapping_mul(1103515245).wrapping_add(12345);
+            let jitter = (noise >> 16) % 21;
+            let value = if on_line || in_digit { 30 } else { 200 };
+            image::Luma([(value + jitter) as u8])
+        })
+    }
+
+    #[test]
+    fn reads_a_straight_grid() {
+        let puzzle: Numbers = PUZZLE.parse().unwrap();
+        let image = photo(
+            &puzzle,
+            400,
+            400,
+            [(20.0, 20.0), (380.0, 20.0), (380.0, 380.0), (20.0, 380.0)],
+        );
+        let recognition = recognize(&image).unwrap();
+        assert_eq!(puzzle, recognition.numbers);
+    }
+
+    #[test]
+    fn reads_a_grid_in_perspective() {
+        let puzzle: Numbers = PUZZLE.parse().unwrap();
+        let image = photo(
+            &puzzle,
+            640,
+            480,
+            [(140.0, 40.0), (520.0, 70.0), (560.0, 440.0), (90.0, 420.0)],
+        );
+        let recognition = recognize(&image).unwrap();
+        assert_eq!(puzzle, recognition.numbers);
+        assert!(recognition.uncertain().is_empty());
+    }
+
+    #[test]
+    fn rejects_images_without_a_grid() {
+        let blank = GrayImage::from_pixel(200, 200, image::Luma([200]));
+        assert_eq!(Err(OcrError::NoGrid), recognize(&blank));
+        assert!(matches!(
+            recognize_bytes(b"not an image"),
+            Err(OcrError::Decode(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_images_too_small_for_a_grid() {
+        for (width, height) in [(0, 0), (1, 1600), (1600, 1), (8, 8)] {
+            let image = GrayImage::from_pixel(width, height, image::Luma([200]));
+            assert_eq!(
+                Err(OcrError::NoGrid),
+                recognize(&image),
+                "{}x{}",
+                width,
+                height
+            );
+        }
+    }
+
+    #[test]
+    fn homography_maps_the_corners() {
+        let from = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+        let to = [(10.0, 5.0), (50.0, 8.0), (45.0, 60.0), (3.0, 40.0)];
+        let homography = Homography::solve(from, to);
+        for (&from, &(u, v)) in from.iter().zip(&to) {
+            let (x, y) = homography.map(from);
+            assert!((x - u).abs() < 1e-9 && (y - v).abs() < 1e-9);
+        }
+    }
+
+    /// every image in `fixtures/ocr` must be read as the puzzle in the `.txt` file next to it, see
+    /// the readme there for where the images come from
+    #[test]
+    fn reads_the_fixtures() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/ocr");
+        let mut checked = 0;
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if !path
+                .extension()
+                .is_some_and(|ext| ext == "jpg" || ext == "jpeg" || ext == "png")
+            {
+                continue;
+            }
+
+            let expected: Numbers = fs::read_to_string(path.with_extension("txt"))
+                .unwrap()
+                .trim()
+                .parse()
+                .unwrap();
+            let recognition = recognize_bytes(&fs::read(&path).unwrap()).unwrap();
+            assert_eq!(expected, recognition.numbers, "{}", path.display());
+            checked += 1;
+        }
+        assert!(checked > 0);
+    }
+}
@@ -0,0 +1,279 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::bitboard::Candidates;
+use crate::solver::{HiddenSingleStep, NakedSingleStep, Solver, SolverStep, Stuck};
+use crate::sudoku::Numbers;
+use crate::techniques::als::{AlsXyWingStep, AlsXzStep, DeathBlossomStep};
+use crate::techniques::chains::{
+    AicStep, CellForcingChainStep, NiceLoopStep, UnitForcingChainStep, XyChainStep, MAX_LENGTH,
+};
+use crate::techniques::coloring::{ColorTrapStep, ColorWrapStep, MultiColoringStep};
+use crate::techniques::fish::FishStep;
+use crate::techniques::intersections::{ClaimingStep, PointingStep, SueDeCoqStep};
+use crate::techniques::single_digit::{
+    EmptyRectangleStep, SkyscraperStep, TurbotFishStep, TwoStringKiteStep, XChainStep,
+};
+use crate::techniques::subsets::{HiddenSubsetStep, NakedSubsetStep};
+use crate::techniques::uniqueness::{
+    AvoidableRectangleStep, BugPlusOneStep, RectangleType, UniqueRectangleStep,
+};
+use crate::techniques::wings::{WWingStep, WxyzWingStep, XyWingStep, XyzWingStep};
+
+/// a rough grade of how hard a puzzle is for a human, based on the hardest technique needed
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Difficulty {
+    /// solvable with naked singles only
+    Easy,
+    /// needs hidden singles
+    Medium,
+    /// needs intersections, subsets, fish, single digit patterns, coloring, wings or uniqueness
+    Hard,
+    /// needs sue de coq, chains, almost locked sets or forcing chains
+    Expert,
+    /// needs something beyond the techniques the solver knows
+    Unknown,
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+            Difficulty::Expert => "expert",
+            Difficulty::Unknown => "unknown",
+        })
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "easy" => Ok(Difficulty::Easy),
+            "medium" => Ok(Difficulty::Medium),
+            "hard" => Ok(Difficulty::Hard),
+            "expert" => Ok(Difficulty::Expert),
+            "unknown" => Ok(Difficulty::Unknown),
+            _ => Err(format!("unknown difficulty {:?}", s)),
+        }
+    }
+}
+
+/// the result of [`rate_with`]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Rating {
+    pub difficulty: Difficulty,
+    /// whether the solve needed a technique that relies on the puzzle having a unique solution
+    pub uses_uniqueness: bool,
+}
+
+/// the techniques used for rating, simplest first. the ones that rely on the puzzle having a
+/// unique solution are only used by [`rate_with`] when asked to.
+pub fn techniques() -> [(&'static dyn SolverStep, Difficulty); 43] {
+    [
+        (&NakedSingleStep, Difficulty::Easy),
+        (&HiddenSingleStep, Difficulty::Medium),
+        (&PointingStep, Difficulty::Hard),
+        (&ClaimingStep, Difficulty::Hard),
+        (&NakedSubsetStep(2), Difficulty::Hard),
+        (&HiddenSubsetStep(2), Difficulty::Hard),
+        (&NakedSubsetStep(3), Difficulty::Hard),
+        (&HiddenSubsetStep(3), Difficulty::Hard),
+        (&FishStep(2), Difficulty::Hard),
+        (&FishStep(3), Difficulty::Hard),
+        (&NakedSubsetStep(4), Difficulty::Hard),
+        (&HiddenSubsetStep(4), Difficulty::Hard),
+        (&FishStep(4), Difficulty::Hard),
+        (&SkyscraperStep, Difficulty::Hard),
+        (&TwoStringKiteStep, Difficulty::Hard),
+        (&TurbotFishStep, Difficulty::Hard),
+        (&EmptyRectangleStep, Difficulty::Hard),
+        (&XyWingStep, Difficulty::Hard),
+        (&XyzWingStep, Difficulty::Hard),
+        (&WWingStep, Difficulty::Hard),
+        (&WxyzWingStep, Difficulty::Hard),
+        (&UniqueRectangleStep(RectangleType::One), Difficulty::Hard),
+        (&UniqueRectangleStep(RectangleType::Two), Difficulty::Hard),
+        (&UniqueRectangleStep(RectangleType::Three), Difficulty::Hard),
+        (&UniqueRectangleStep(RectangleType::Four), Difficulty::Hard),
+        (&UniqueRectangleStep(RectangleType::Five), Difficulty::Hard),
+        (&UniqueRectangleStep(RectangleType::Six), Difficulty::Hard),
+        (
+            &UniqueRectangleStep(RectangleType::Hidden),
+            Difficulty::Hard,
+        ),
+        (&AvoidableRectangleStep, Difficulty::Hard),
+        (&BugPlusOneStep, Difficulty::Hard),
+        (&ColorWrapStep, Difficulty::Hard),
+        (&ColorTrapStep, Difficulty::Hard),
+        (&MultiColoringStep, Difficulty::Hard),
+        (&SueDeCoqStep, Difficulty::Expert),
+        (&XChainStep, Difficulty::Expert),
+        (
+            &XyChainStep {
+                max_length: MAX_LENGTH,
+            },
+            Difficulty::Expert,
+        ),
+        (
+            &AicStep {
+                max_length: MAX_LENGTH,
+            },
+            Difficulty::Expert,
+        ),
+        (
+            &NiceLoopStep {
+                max_length: MAX_LENGTH,
+            },
+            Difficulty::Expert,
+        ),
+        (&AlsXzStep, Difficulty::Expert),
+        (&AlsXyWingStep, Difficulty::Expert),
+        (&DeathBlossomStep, Difficulty::Expert),
+        (
+            &CellForcingChainStep {
+                max_length: MAX_LENGTH,
+            },
+            Difficulty::Expert,
+        ),
+        (
+            &UnitForcingChainStep {
+                max_length: MAX_LENGTH,
+            },
+            Difficulty::Expert,
+        ),
+    ]
+}
+
+/// rates a puzzle by solving it like a human would: always apply the simplest technique that
+/// makes progress and remember the hardest one that was needed.
+///
+/// the puzzle is expected to be valid and to have a unique solution.
+pub fn rate(numbers: &Numbers) -> Difficulty {
+    rate_with(numbers, false).difficulty
+}
+
+/// rates a puzzle like [`rate`], and with `assume_uniqueness` also lets the solve use the
+/// techniques that rely on the puzzle having a unique solution
+pub fn rate_with(numbers: &Numbers, assume_uniqueness: bool) -> Rating {
+    let techniques = techniques();
+    let mut solver = Solver::default();
+    solver.set_assume_uniqueness(assume_uniqueness);
+    for (step, _) in techniques {
+        solver.add_step(step);
+    }
+
+    let mut hardest = Difficulty::Easy;
+    let mut uses_uniqueness = false;
+    let result = solver.run(&mut Candidates::from_numbers(numbers), |applied| {
+        hardest = hardest.max(techniques[applied.index].1);
+        uses_uniqueness |= applied.step.needs_uniqueness();
+    });
+
+    Rating {
+        difficulty: match result {
+            Ok(()) => hardest,
+            Err(Stuck) => Difficulty::Unknown,
+        },
+        uses_uniqueness,
+    }
+}
+
+/// solves a puzzle with the [`techniques`] up to and including the one at `max`, and returns
+/// the indexes of the ones it used, simplest first. `None` if that's not enough to solve it.
+pub fn techniques_used(
+    numbers: &Numbers,
+    max: usize,
+    assume_uniqueness: bool,
+) -> Option<Vec<usize>> {
+    let techniques = techniques();
+    let mut solver = Solver::default();
+    solver.set_assume_uniqueness(assume_uniqueness);
+    for (step, _) in &techniques[..=max] {
+        solver.add_step(*step);
+    }
+
+    let mut used = vec![false; techniques.len()];
+    let result = solver.run(&mut Candidates::from_numbers(numbers), |applied| {
+        used[applied.index] = true;
+    });
+
+    result
+        .ok()
+        .map(|()| (0..used.len()).filter(|&idx| used[idx]).collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rates_singles_puzzle() {
+        let puzzle: Numbers =
+            "1...6....98....6.5.....5..1......3.4.6.13.9...4.72.....93.761....648...75..9.246."
+                .parse()
+                .unwrap();
+        assert!(rate(&puzzle) <= Difficulty::Medium);
+        assert!(!rate_with(&puzzle, true).uses_uniqueness);
+    }
+
+    #[test]
+    fn rates_chains_at_least_as_hard_as_sue_de_coq() {
+        let chain: Numbers =
+            "456...9.........6..9.4...3.1....8......7.4....8.15......4..5....673....83...6..45"
+                .parse()
+                .unwrap();
+        let sue_de_coq: Numbers =
+            "..5..7..4....3.72...7..1.3...18..67.3....9....46....1......45.348..........7....."
+                .parse()
+                .unwrap();
+        let techniques = techniques();
+        let name = |idx: usize| techniques[idx].0.name();
+        let last = techniques.len() - 1;
+        let chain_used = techniques_used(&chain, last, false).unwrap();
+        assert!(chain_used.iter().any(|&idx| name(idx) == "XY-Chain"));
+        assert!(chain_used.iter().all(|&idx| name(idx) != "Sue de Coq"));
+        let sue_de_coq_used = techniques_used(&sue_de_coq, last, false).unwrap();
+        assert_eq!("Sue de Coq", name(*sue_de_coq_used.last().unwrap()));
+
+        assert_eq!(Difficulty::Expert, rate(&sue_de_coq));
+        assert!(rate(&chain) >= rate(&sue_de_coq));
+    }
+
+    #[test]
+    fn rates_puzzles_the_solver_cannot_finish_as_unknown() {
+        assert_eq!(Difficulty::Unknown, rate(&Numbers::empty()));
+    }
+
+    #[test]
+    fn difficulty_round_trips_through_strings() {
+        for difficulty in [
+            Difficulty::Easy,
+            Difficulty::Medium,
+            Difficulty::Hard,
+            Difficulty::Expert,
+            Difficulty::Unknown,
+        ] {
+            assert_eq!(Ok(difficulty), difficulty.to_string().parse());
+        }
+    }
+
+    #[test]
+    fn reports_the_techniques_used() {
+        let puzzle: Numbers =
+            "1...6....98....6.5.....5..1......3.4.6.13.9...4.72.....93.761....648...75..9.246."
+                .parse()
+                .unwrap();
+        let used = techniques_used(&puzzle, 1, false).unwrap();
+        assert!(used.iter().all(|&idx| idx <= 1));
+        assert_eq!(None, techniques_used(&Numbers::empty(), 0, false));
+    }
+}
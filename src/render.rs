@@ -0,0 +1,390 @@
+//! rendering of games as SVG and of puzzle booklets as PDF, independent of the DOM so it can
+//! be used natively.
+//!
+//! both outputs are drawn by the same code through a small [`Canvas`] with a y axis pointing
+//! down, the PDF backend flips it. the PDF only uses the standard Helvetica fonts, so it needs
+//! no embedded font data.
+
+use std::fmt::Write;
+
+use crate::sudoku::{Game, Numbers};
+
+/// what to draw besides the givens, and how large
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderOptions {
+    /// draw the numbers entered by the player
+    pub entries: bool,
+    /// draw the notes of empty cells
+    pub notes: bool,
+    /// width of a cell, in px for SVG and pt for PDF
+    pub cell_size: f64,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            entries: true,
+            notes: true,
+            cell_size: 40.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Style {
+    Given,
+    Entry,
+    Note,
+    Label,
+}
+
+impl Style {
+    fn color(self) -> (f64, f64, f64) {
+        match self {
+            Style::Given | Style::Label => (0.0, 0.0, 0.0),
+            Style::Entry => (0.1, 0.3, 0.8),
+            Style::Note => (0.4, 0.4, 0.4),
+        }
+    }
+
+    fn bold(self) -> bool {
+        self == Style::Given
+    }
+}
+
+/// the drawing primitives the grid needs, in a coordinate system with y pointing down
+trait Canvas {
+    fn line(&mut self, from: (f64, f64), to: (f64, f64), width: f64);
+    /// draws text with its baseline at `at.1`, centered on `at.0` or starting there
+    fn text(&mut self, at: (f64, f64), size: f64, text: &str, style: Style, centered: bool);
+
+    /// draws a single digit centered on a point
+    fn digit(&mut self, center: (f64, f64), size: f64, digit: u8, style: Style) {
+        // digits are about 0.7em high, so this puts their middle on the point
+        let baseline = center.1 + size * 0.35;
+        self.text((center.0, baseline), size, &digit.to_string(), style, true);
+    }
+}
+
+/// the width of the thick box borders for a cell size
+fn thick_line(cell: f64) -> f64 {
+    cell * 0.06
+}
+
+fn draw_game(canvas: &mut impl Canvas, origin: (f64, f64), game: &Game, options: &RenderOptions) {
+    let cell = options.cell_size;
+    let (x, y) = origin;
+    let size = cell * 9.0;
+
+    for i in 0..=9 {
+        let width = if i % 3 == 0 {
+            thick_line(cell)
+        } else {
+            cell * 0.02
+        };
+        let offset = i as f64 * cell;
+        canvas.line((x, y + offset), (x + size, y + offset), width);
+        canvas.line((x + offset, y), (x + offset, y + size), width);
+    }
+
+    for idx in 0..81 {
+        let (row, col) = Game::cell_index_to_coords(idx);
+        let left = x + (idx % 9) as f64 * cell;
+        let top = y + (idx / 9) as f64 * cell;
+        let center = (left + cell / 2.0, top + cell / 2.0);
+
+        match game.get(row, col) {
+            0 if options.notes => {
+                let notes = game.get_notes(row, col);
+                for digit in (1..=9u8).filter(|digit| notes & (1 << (digit - 1)) != 0) {
+                    let sub = (digit - 1) as f64;
+                    let center = (
+                        left + cell * ((sub % 3.0) * 2.0 + 1.0) / 6.0,
+                        top + cell * ((sub / 3.0).floor() * 2.0 + 1.0) / 6.0,
+                    );
+                    canvas.digit(center, cell * 0.25, digit, Style::Note);
+                }
+            }
+            0 => {}
+            value if game.is_given(row, col) => {
+                canvas.digit(center, cell * 0.6, value, Style::Given)
+            }
+            value if options.entries => canvas.digit(center, cell * 0.6, value, Style::Entry),
+            _ => {}
+        }
+    }
+}
+
+#[derive(Default)]
+struct Svg {
+    body: String,
+}
+
+impl Canvas for Svg {
+    fn line(&mut self, from: (f64, f64), to: (f64, f64), width: f64) {
+        let _ = writeln!(
+            self.body,
+            r#"<line x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}" stroke="black" stroke-width="{:.2}" stroke-linecap="square"/>"#,
+            from.0, from.1, to.0, to.1, width
+        );
+    }
+
+    fn text(&mut self, at: (f64, f64), size: f64, text: &str, style: Style, centered: bool) {
+        let (r, g, b) = style.color();
+        let _ = writeln!(
+            self.body,
+            r#"<text x="{:.2}" y="{:.2}" font-size="{:.2}" font-weight="{}" text-anchor="{}" fill="rgb({:.0},{:.0},{:.0})">{}</text>"#,
+            at.0,
+            at.1,
+            size,
+            if style.bold() { "bold" } else { "normal" },
+            if centered { "middle" } else { "start" },
+            r * 255.0,
+            g * 255.0,
+            b * 255.0,
+            text.replace('&', "&amp;").replace('<', "&lt;"),
+        );
+    }
+}
+
+/// renders a game as a standalone SVG image
+pub fn svg(game: &Game, options: &RenderOptions) -> String {
+    // leave room for the outer border, which is centered on the edge of the grid
+    let margin = thick_line(options.cell_size) / 2.0;
+    let size = options.cell_size * 9.0 + margin * 2.0;
+
+    let mut canvas = Svg::default();
+    draw_game(&mut canvas, (margin, margin), game, options);
+
+    format!(
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{size:.2}" height="{size:.2}" "#,
+            r#"viewBox="0 0 {size:.2} {size:.2}" font-family="Helvetica, Arial, sans-serif">"#,
+            "\n{body}</svg>\n"
+        ),
+        size = size,
+        body = canvas.body
+    )
+}
+
+/// an A4 page, in pt
+const PAGE_SIZE: (f64, f64) = (595.0, 842.0);
+const PAGE_MARGIN: f64 = 50.0;
+
+/// the content stream of a single PDF page
+struct PdfPage {
+    content: String,
+}
+
+impl Canvas for PdfPage {
+    fn line(&mut self, from: (f64, f64), to: (f64, f64), width: f64) {
+        let _ = writeln!(
+            self.content,
+            "{:.2} w {:.2} {:.2} m {:.2} {:.2} l S",
+            width,
+            from.0,
+            PAGE_SIZE.1 - from.1,
+            to.0,
+            PAGE_SIZE.1 - to.1
+        );
+    }
+
+    fn text(&mut self, at: (f64, f64), size: f64, text: &str, style: Style, centered: bool) {
+        // every digit and space of Helvetica is 0.556em and 0.278em wide, which is close
+        // enough for the short labels as well
+        let width: f64 = text
+            .chars()
+            .map(|c| if c == ' ' { 0.278 } else { 0.556 })
+            .sum::<f64>()
+            * size;
+        let x = if centered { at.0 - width / 2.0 } else { at.0 };
+        let (r, g, b) = style.color();
+        let text = text
+            .replace('\\', "\\\\")
+            .replace('(', "\\(")
+            .replace(')', "\\)");
+
+        let _ = writeln!(
+            self.content,
+            "BT /{} {:.2} Tf {:.2} {:.2} {:.2} rg {:.2} {:.2} Td ({}) Tj ET",
+            if style.bold() { "F2" } else { "F1" },
+            size,
+            r,
+            g,
+            b,
+            x,
+            PAGE_SIZE.1 - at.1,
+            text
+        );
+    }
+}
+
+/// places titled grids in a table of `cols` x `rows` per page
+fn lay_out(
+    pages: &mut Vec<PdfPage>,
+    grids: &[(String, Game)],
+    (cols, rows): (usize, usize),
+    options: &RenderOptions,
+) {
+    let label_size = options.cell_size * 0.8;
+    let cell_width = (PAGE_SIZE.0 - PAGE_MARGIN * 2.0) / cols as f64;
+    let cell_height = (PAGE_SIZE.1 - PAGE_MARGIN * 2.0) / rows as f64;
+
+    for page_grids in grids.chunks(cols * rows) {
+        // projecting line caps, so the box borders meet in square corners
+        let mut page = PdfPage {
+            content: String::from("2 J\n"),
+        };
+        for (i, (title, game)) in page_grids.iter().enumerate() {
+            let x = PAGE_MARGIN + (i % cols) as f64 * cell_width;
+            let y = PAGE_MARGIN + (i / cols) as f64 * cell_height;
+            page.text((x, y + label_size), label_size, title, Style::Label, false);
+            draw_game(&mut page, (x, y + label_size * 1.6), game, options);
+        }
+        pages.push(page);
+    }
+}
+
+/// writes the pages with the page tree, the two fonts and the cross-reference table
+fn write_pdf(pages: &[PdfPage]) -> Vec<u8> {
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            (0..pages.len())
+                .map(|i| format!("{} 0 R", 5 + i * 2))
+                .collect::<Vec<_>>()
+                .join(" "),
+            pages.len()
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold >>".to_string(),
+    ];
+    for (i, page) in pages.iter().enumerate() {
+        objects.push(format!(
+            concat!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] ",
+                "/Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>"
+            ),
+            PAGE_SIZE.0,
+            PAGE_SIZE.1,
+            6 + i * 2
+        ));
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            page.content.len(),
+            page.content
+        ));
+    }
+
+    let mut out = String::from("%PDF-1.4\n");
+    let mut offsets = vec![];
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(out.len());
+        let _ = write!(out, "{} 0 obj\n{}\nendobj\n", i + 1, object);
+    }
+
+    let xref = out.len();
+    let _ = write!(out, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        let _ = writeln!(out, "{:010} 00000 n ", offset);
+    }
+    let _ = write!(
+        out,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref
+    );
+
+    out.into_bytes()
+}
+
+/// lays out puzzles six to an A4 page, followed by pages with the answer keys, twelve to a page.
+/// the puzzles are given together with their solutions.
+pub fn booklet(puzzles: &[(Numbers, Numbers)]) -> Vec<u8> {
+    let grids = |title: &str, pick: fn(&(Numbers, Numbers)) -> Numbers| -> Vec<(String, Game)> {
+        puzzles
+            .iter()
+            .enumerate()
+            .map(|(i, pair)| (format!("{} {}", title, i + 1), Game::create(pick(pair))))
+            .collect()
+    };
+
+    let mut pages = vec![];
+    let puzzle_options = RenderOptions {
+        cell_size: 24.0,
+        ..RenderOptions::default()
+    };
+    lay_out(
+        &mut pages,
+        &grids("Puzzle", |(puzzle, _)| *puzzle),
+        (2, 3),
+        &puzzle_options,
+    );
+
+    let answer_options = RenderOptions {
+        cell_size: 14.0,
+        ..RenderOptions::default()
+    };
+    lay_out(
+        &mut pages,
+        &grids("Solution", |(_, solution)| *solution),
+        (3, 4),
+        &answer_options,
+    );
+
+    write_pdf(&pages)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PUZZLE: &str =
+        "1...6....98....6.5.....5..1......3.4.6.13.9...4.72.....93.761....648...75..9.246.";
+
+    fn game() -> Game {
+        let mut game = Game::create(PUZZLE.parse::<Numbers>().unwrap());
+        game.set(1, 2, 2);
+        game.set_notes(1, 3, 0b0_0100_0101);
+        game
+    }
+
+    #[test]
+    fn svg_draws_grid_and_numbers() {
+        let svg = svg(&game(), &RenderOptions::default());
+        assert!(svg.starts_with("<svg"));
+        assert_eq!(20, svg.matches("<line").count());
+        // 31 givens, one entry and three notes
+        assert_eq!(35, svg.matches("<text").count());
+        assert_eq!(31, svg.matches(r#"font-weight="bold""#).count());
+    }
+
+    #[test]
+    fn svg_can_leave_out_progress() {
+        let options = RenderOptions {
+            entries: false,
+            notes: false,
+            ..RenderOptions::default()
+        };
+        assert_eq!(31, svg(&game(), &options).matches("<text").count());
+    }
+
+    #[test]
+    fn booklet_has_puzzle_and_answer_pages() {
+        let puzzle: Numbers = PUZZLE.parse().unwrap();
+        let solution = match crate::brute_force::solve(&puzzle) {
+            crate::brute_force::Solutions::Unique(solution) => solution,
+            _ => unreachable!(),
+        };
+
+        let pdf = String::from_utf8(booklet(&vec![(puzzle, solution); 7])).unwrap();
+        assert!(pdf.starts_with("%PDF-1.4\n") && pdf.ends_with("%%EOF\n"));
+        assert!(pdf.contains("/Count 3 >>"));
+        assert_eq!(7, pdf.matches("(Solution ").count());
+
+        let startxref = pdf.rsplit("startxref\n").next().unwrap();
+        let xref: usize = startxref.lines().next().unwrap().parse().unwrap();
+        assert!(pdf[xref..].starts_with("xref\n"));
+    }
+}
@@ -0,0 +1,318 @@
+//! a numeric rating on the scale of sudoku explainer (se), which is how puzzles are usually
+//! compared: 1.5 for a hidden single in a line, 2.6 for pointing, 7.x for chains and so on.
+//!
+//! like se, the solve always applies the technique with the lowest rating that makes progress,
+//! and the puzzle gets the rating of the hardest step. techniques that se doesn't know are
+//! rated like the se technique that finds the same eliminations. chains get a bonus for their
+//! length, on the same steps as in se.
+//!
+//! the ratings can't be guaranteed to match se's. se compares the ratings of all the steps it
+//! finds, including the bonus, where this solve takes the first technique by base rating, so a
+//! step with a long chain can come out a little higher than in se. the chains found also
+//! differ from se's, and so do their lengths, and a technique rated like an se one may make
+//! progress where se needs something harder. only the reference puzzles marked as rated by se
+//! are known to agree.
+//!
+//! this sits alongside the simpler [`Difficulty`](crate::rating::Difficulty) grade.
+
+use std::fmt;
+
+use crate::bitboard::{Candidates, HOUSES};
+use crate::solver::{NakedSingleStep, PatternPart, Solver, SolverStep};
+use crate::sudoku::Numbers;
+use crate::techniques::als::{AlsXyWingStep, AlsXzStep, DeathBlossomStep};
+use crate::techniques::chains::{
+    AicStep, CellForcingChainStep, NiceLoopStep, UnitForcingChainStep, XyChainStep, MAX_LENGTH,
+};
+use crate::techniques::coloring::{ColorTrapStep, ColorWrapStep, MultiColoringStep};
+use crate::techniques::fish::FishStep;
+use crate::techniques::intersections::{ClaimingStep, PointingStep, SueDeCoqStep};
+use crate::techniques::single_digit::{
+    EmptyRectangleStep, SkyscraperStep, TurbotFishStep, TwoStringKiteStep, XChainStep,
+};
+use crate::techniques::subsets::{HiddenSubsetStep, NakedSubsetStep};
+use crate::techniques::uniqueness::{
+    AvoidableRectangleStep, BugPlusOneStep, RectangleType, UniqueRectangleStep,
+};
+use crate::techniques::wings::{WWingStep, WxyzWingStep, XyWingStep, XyzWingStep};
+
+/// a rating on the se scale, in tenths: 15 is 1.5
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub struct SeRating(pub u32);
+
+impl fmt::Display for SeRating {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.0 / 10, self.0 % 10)
+    }
+}
+
+/// hidden singles in boxes or in lines only, which se rates apart
+#[derive(Debug)]
+struct HiddenSingleIn {
+    boxes: bool,
+}
+
+impl SolverStep for HiddenSingleIn {
+    fn name(&self) -> &'static str {
+        if self.boxes {
+            "Hidden Single in Box"
+        } else {
+            "Hidden Single in Line"
+        }
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        let houses = if self.boxes {
+            &HOUSES[18..]
+        } else {
+            &HOUSES[..18]
+        };
+
+        let mut changed = false;
+        for &house in houses {
+            for digit in 1..=9u8 {
+                let cells = candidates.digit(digit) & house;
+                if cells.count() == 1 {
+                    candidates.place(cells.first().unwrap(), digit);
+                    changed = true;
+                }
+            }
+        }
+
+        changed
+    }
+}
+
+/// a step that only counts when it leaves a hidden single of a digit it removed, in a house it
+/// removed the digit from, which se rates lower than the step on its own
+#[derive(Debug)]
+struct Direct {
+    step: &'static dyn SolverStep,
+    name: &'static str,
+}
+
+impl SolverStep for Direct {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        let mut after = *candidates;
+        if !self.step.apply_candidates(&mut after) {
+            return false;
+        }
+
+        let direct = (1..=9u8).any(|digit| {
+            let removed = candidates.digit(digit) & !after.digit(digit);
+            HOUSES.iter().any(|&house| {
+                !(removed & house).is_empty() && (after.digit(digit) & house).count() == 1
+            })
+        });
+        if direct {
+            *candidates = after;
+        }
+
+        direct
+    }
+
+    fn pattern(&self, candidates: &Candidates) -> Vec<PatternPart> {
+        self.step.pattern(candidates)
+    }
+}
+
+/// the techniques in the order of their base rating on the se scale, in tenths
+pub fn techniques() -> [(&'static dyn SolverStep, u32); 48] {
+    [
+        (&HiddenSingleIn { boxes: true }, 12),
+        (&HiddenSingleIn { boxes: false }, 15),
+        (
+            &Direct {
+                step: &PointingStep,
+                name: "Direct Pointing",
+            },
+            17,
+        ),
+        (
+            &Direct {
+                step: &ClaimingStep,
+                name: "Direct Claiming",
+            },
+            19,
+        ),
+        (
+            &Direct {
+                step: &HiddenSubsetStep(2),
+                name: "Direct Hidden Pair",
+            },
+            20,
+        ),
+        (&NakedSingleStep, 23),
+        (
+            &Direct {
+                step: &HiddenSubsetStep(3),
+                name: "Direct Hidden Triple",
+            },
+            25,
+        ),
+        (&PointingStep, 26),
+        (&ClaimingStep, 28),
+        (&NakedSubsetStep(2), 30),
+        (&FishStep(2), 32),
+        (&HiddenSubsetStep(2), 34),
+        (&NakedSubsetStep(3), 36),
+        (&FishStep(3), 38),
+        (&HiddenSubsetStep(3), 40),
+        (&XyWingStep, 42),
+        (&XyzWingStep, 44),
+        (&UniqueRectangleStep(RectangleType::One), 45),
+        (&UniqueRectangleStep(RectangleType::Two), 46),
+        (&UniqueRectangleStep(RectangleType::Four), 46),
+        (&UniqueRectangleStep(RectangleType::Three), 47),
+        (&UniqueRectangleStep(RectangleType::Five), 47),
+        (&UniqueRectangleStep(RectangleType::Six), 47),
+        (&UniqueRectangleStep(RectangleType::Hidden), 47),
+        (&AvoidableRectangleStep, 47),
+        (&NakedSubsetStep(4), 50),
+        (&FishStep(4), 52),
+        (&HiddenSubsetStep(4), 54),
+        (&BugPlusOneStep, 56),
+        (&WxyzWingStep, 62),
+        (&ColorWrapStep, 65),
+        (&ColorTrapStep, 65),
+        (&SkyscraperStep, 66),
+        (&TwoStringKiteStep, 66),
+        (&TurbotFishStep, 66),
+        (&EmptyRectangleStep, 66),
+        (&MultiColoringStep, 66),
+        (&XChainStep, 66),
+        (
+            &XyChainStep {
+                max_length: MAX_LENGTH,
+            },
+            66,
+        ),
+        (&WWingStep, 70),
+        (
+            &AicStep {
+                max_length: MAX_LENGTH,
+            },
+            70,
+        ),
+        (
+            &NiceLoopStep {
+                max_length: MAX_LENGTH,
+            },
+            70,
+        ),
+        (&AlsXzStep, 75),
+        (&SueDeCoqStep, 75),
+        (&AlsXyWingStep, 78),
+        (&DeathBlossomStep, 82),
+        (
+            &CellForcingChainStep {
+                max_length: MAX_LENGTH,
+            },
+            83,
+        ),
+        (
+            &UnitForcingChainStep {
+                max_length: MAX_LENGTH,
+            },
+            83,
+        ),
+    ]
+}
+
+/// the bonus in tenths for a chain of `nodes` nodes: one tenth for every step of the length,
+/// where the steps grow by half and by a third in turn, as in se
+pub fn length_bonus(nodes: usize) -> u32 {
+    let length = nodes.saturating_sub(2);
+    let mut bonus = 0;
+    let mut ceiling = 4;
+    let mut odd = false;
+    while length > ceiling {
+        bonus += 1;
+        ceiling = if odd {
+            ceiling * 4 / 3
+        } else {
+            ceiling * 3 / 2
+        };
+        odd = !odd;
+    }
+
+    bonus
+}
+
+/// rates a puzzle on the se scale, or returns `None` if it needs more than the solver knows.
+/// the uniqueness techniques are used, as se does.
+pub fn rate(numbers: &Numbers) -> Option<SeRating> {
+    let techniques = techniques();
+    let mut solver = Solver::default();
+    solver.set_assume_uniqueness(true);
+    for (step, _) in techniques {
+        solver.add_step(step);
+    }
+
+    let mut hardest = 0;
+    let result = solver.run(&mut Candidates::from_numbers(numbers), |applied| {
+        let nodes = applied
+            .step
+            .chain(applied.before)
+            .into_iter()
+            .chain(applied.step.branches(applied.before))
+            .map(|chain| chain.nodes.len())
+            .sum();
+        let rating = techniques[applied.index].1 + if nodes > 0 { length_bonus(nodes) } else { 0 };
+        hardest = hardest.max(rating);
+    });
+
+    result.ok().map(|()| SeRating(hardest))
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::path::Path;
+
+    use super::*;
+
+    #[test]
+    fn techniques_are_in_order_of_their_rating() {
+        let techniques = techniques();
+        assert!(techniques.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+    }
+
+    #[test]
+    fn longer_chains_get_a_bonus() {
+        let bonuses: Vec<u32> = [4, 6, 7, 8, 9, 10, 11, 14, 15, 18, 19, 26, 27, 34, 35]
+            .into_iter()
+            .map(length_bonus)
+            .collect();
+        assert_eq!(vec![0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7], bonuses);
+
+        // se's ceilings on the length, which is two less than the nodes
+        for (bonus, ceiling) in [4, 6, 8, 12, 16, 24, 32].into_iter().enumerate() {
+            assert_eq!(bonus as u32, length_bonus(ceiling + 2));
+            assert_eq!(bonus as u32 + 1, length_bonus(ceiling + 3));
+        }
+    }
+
+    /// every puzzle in `fixtures/se_ratings.txt` must get the rating next to it, or none
+    #[test]
+    fn rates_the_reference_puzzles() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/se_ratings.txt");
+        let mut checked = 0;
+        for line in fs::read_to_string(path).unwrap().lines() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (puzzle, expected) = line.split_once(' ').unwrap();
+            let rating = rate(&puzzle.parse().unwrap())
+                .map_or_else(|| "none".to_string(), |rating| rating.to_string());
+            assert_eq!(expected.trim(), rating, "{}", puzzle);
+            checked += 1;
+        }
+        assert!(checked > 0);
+    }
+}
@@ -0,0 +1,246 @@
+//! serde support for the model and the solver results, enabled with the `serde` feature.
+//!
+//! the layouts below are stable, new fields may be added but existing ones won't change.
+//!
+//! - [`Numbers`]: a string in the line format, `.` for empty cells, e.g. `"1...6....98…"`
+//! - [`Game`]: an object with the givens, the current numbers (including the givens) and the
+//!   81 notes masks, where bit 0 is digit 1:
+//!   `{"givens": "1...", "current": "12..", "notes": [0, 0, 289, …]}`
+//! - [`Difficulty`](crate::rating::Difficulty): `"easy"`, `"medium"`, `"hard"`, `"expert"` or
+//!   `"unknown"`
+//! - [`Solutions`](crate::brute_force::Solutions): `{"solutions": "unique", "solution": "…"}`,
+//!   `{"solutions": "none"}` or `{"solutions": "multiple"}`
+//! - [`Outcome`]: `{"outcome": "solved", "solution": "…"}`,
+//!   `{"outcome": "rated", "puzzle": "…", "difficulty": "easy"}` or
+//!   `{"outcome": "unique", "puzzle": "…"}`
+//! - [`Problem`]: `{"problem": "invalid", "message": "invalid puzzle: …"}`, where `problem` is
+//!   the same label the `check` command prints
+//! - [`Record`]: the fields `line` and `input` (the puzzle as found in the input), plus the
+//!   fields of its outcome or problem
+//! - [`Stats`]: `{"puzzles": 10, "failures": 0, "elapsed_secs": 0.1, "puzzles_per_second": 100.0}`
+//! - [`SolveLog`](crate::solver::SolveLog): the starting game and the entries in order,
+//!   `{"start": {…}, "entries": [{"technique": "Naked Single", "placements": [{"cell": 2,
+//!   "digit": 5}], "eliminations": [], "explanation": "…"}]}`, where cells are indices 0-80;
+//!   entries of chain techniques also carry `"chain": {"nodes": [{"digit": 5, "cells": [1]}, …],
+//!   "links": ["strong", "weak", …]}` (forcing chains carry a list of them as `"branches"`),
+//!   and entries of techniques that name the parts of their pattern carry
+//!   `"pattern": [{"role": "pivot", "cells": [40], "digits": [1, 2]}, …]`
+//!
+//! for a single string, [`Game`] also has a compact form through its `Display` and `FromStr`
+//! implementations.
+
+use serde::de::{self, Deserializer};
+use serde::ser::{SerializeMap, Serializer};
+use serde::{Deserialize, Serialize};
+
+use crate::batch::{Outcome, Problem, Record, Stats};
+use crate::sudoku::{Game, Numbers};
+
+impl Serialize for Numbers {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Numbers {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let line = String::deserialize(deserializer)?;
+        line.parse().map_err(de::Error::custom)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct GameRepr {
+    givens: Numbers,
+    current: Numbers,
+    notes: Vec<u16>,
+}
+
+impl Serialize for Game {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let notes = (0..81)
+            .map(|idx| {
+                let (row, col) = Game::cell_index_to_coords(idx);
+                self.get_notes(row, col)
+            })
+            .collect();
+
+        GameRepr {
+            givens: self.given_numbers(),
+            current: self.current_numbers(),
+            notes,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Game {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = GameRepr::deserialize(deserializer)?;
+        if repr.notes.len() != 81 {
+            return Err(de::Error::invalid_length(repr.notes.len(), &"81 notes"));
+        }
+
+        let mut game = Game::create(repr.givens);
+        for (idx, (given, current)) in repr.givens.cells().zip(repr.current.cells()).enumerate() {
+            let (row, col) = Game::cell_index_to_coords(idx);
+            if given != 0 && given != current {
+                return Err(de::Error::custom(format!(
+                    "current number at cell {} differs from the given",
+                    idx + 1
+                )));
+            }
+
+            game.set(row, col, current);
+            game.try_set_notes(row, col, repr.notes[idx])
+                .map_err(de::Error::custom)?;
+        }
+
+        Ok(game)
+    }
+}
+
+impl Serialize for Problem {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry("problem", self.label())?;
+        map.serialize_entry("message", &self.to_string())?;
+        map.end()
+    }
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum ResultRepr<'a> {
+    Outcome(&'a Outcome),
+    Problem(&'a Problem),
+}
+
+#[derive(Serialize)]
+struct RecordRepr<'a> {
+    line: usize,
+    input: &'a str,
+    #[serde(flatten)]
+    result: ResultRepr<'a>,
+}
+
+impl Serialize for Record {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RecordRepr {
+            line: self.line_no,
+            input: &self.puzzle,
+            result: match &self.result {
+                Ok(outcome) => ResultRepr::Outcome(outcome),
+                Err(problem) => ResultRepr::Problem(problem),
+            },
+        }
+        .serialize(serializer)
+    }
+}
+
+impl Serialize for Stats {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(4))?;
+        map.serialize_entry("puzzles", &self.puzzles)?;
+        map.serialize_entry("failures", &self.failures)?;
+        map.serialize_entry("elapsed_secs", &self.elapsed.as_secs_f64())?;
+        map.serialize_entry("puzzles_per_second", &self.puzzles_per_second())?;
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::batch::Task;
+    use crate::brute_force::{self, Solutions};
+    use crate::rating::Difficulty;
+
+    const PUZZLE: &str =
+        "1...6....98....6.5.....5..1......3.4.6.13.9...4.72.....93.761....648...75..9.246.";
+
+    #[test]
+    fn numbers_are_strings() {
+        let numbers: Numbers = PUZZLE.parse().unwrap();
+        assert_eq!(json!(PUZZLE), serde_json::to_value(numbers).unwrap());
+        assert_eq!(numbers, serde_json::from_value(json!(PUZZLE)).unwrap());
+        assert!(serde_json::from_value::<Numbers>(json!("123")).is_err());
+    }
+
+    #[test]
+    fn game_round_trips() {
+        let mut game = Game::create(PUZZLE.parse::<Numbers>().unwrap());
+        game.set(1, 2, 2);
+        game.set_notes(1, 3, 0x0121);
+
+        let value = serde_json::to_value(game).unwrap();
+        assert_eq!(json!(PUZZLE), value["givens"]);
+        assert_eq!(json!(0x0121), value["notes"][2]);
+        assert_eq!(game, serde_json::from_value(value).unwrap());
+    }
+
+    #[test]
+    fn game_rejects_overwritten_givens() {
+        let value = json!({
+            "givens": PUZZLE,
+            "current": format!("2{}", &PUZZLE[1..]),
+            "notes": vec![0; 81],
+        });
+        assert!(serde_json::from_value::<Game>(value).is_err());
+    }
+
+    #[test]
+    fn solve_log_round_trips() {
+        let mut solver = crate::solver::Solver::default();
+        solver.add_step(crate::solver::NakedSingleStep);
+        let log = solver.solve_log(Game::create(PUZZLE.parse::<Numbers>().unwrap()));
+
+        let value = serde_json::to_value(&log).unwrap();
+        assert_eq!(json!("Naked Single"), value["entries"][0]["technique"]);
+        assert!(value["entries"][0]["placements"][0]["digit"].is_u64());
+        assert_eq!(log, serde_json::from_value(value).unwrap());
+    }
+
+    #[test]
+    fn results_are_tagged() {
+        assert_eq!(
+            json!("medium"),
+            serde_json::to_value(Difficulty::Medium).unwrap()
+        );
+        assert_eq!(
+            json!({"solutions": "multiple"}),
+            serde_json::to_value(brute_force::solve(&Numbers::empty())).unwrap()
+        );
+        assert!(matches!(
+            serde_json::from_value(json!({"solutions": "none"})).unwrap(),
+            Solutions::None
+        ));
+
+        let record = Record {
+            line_no: 3,
+            puzzle: PUZZLE.into(),
+            result: Task::Check.run(PUZZLE),
+        };
+        assert_eq!(
+            json!({"line": 3, "input": PUZZLE, "outcome": "unique", "puzzle": PUZZLE}),
+            serde_json::to_value(record).unwrap()
+        );
+
+        let record = Record {
+            line_no: 4,
+            puzzle: "123".into(),
+            result: Task::Check.run("123"),
+        };
+        assert_eq!(
+            json!({
+                "line": 4,
+                "input": "123",
+                "problem": "invalid",
+                "message": "invalid puzzle: expected 81 cells, found 3",
+            }),
+            serde_json::to_value(record).unwrap()
+        );
+    }
+}
@@ -1,26 +1,55 @@
-use std::fmt::Debug;
+use std::fmt::{self, Debug};
 
 use log::info;
 
-use crate::sudoku;
+use crate::bitboard::{Bitboard, Candidates, HOUSES, PEERS};
 
 use super::sudoku::Game;
 
-#[derive(Debug)]
-pub struct Solver {
-    steps: Vec<Box<dyn SolverStep>>,
-    current_step: Option<usize>,
+/// the order in which a [`Solver`] tries its steps
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Strategy {
+    /// apply the first step that makes progress, then start over with the first step. this is
+    /// how human solvers and the rater work, as it only falls back to harder techniques when
+    /// the simpler ones are exhausted.
+    #[default]
+    SimplestFirst,
+    /// apply one step until it makes no more progress, then move on to the next one
+    Fixpoint,
+    /// apply every step once, in order
+    Exhaustive,
 }
 
-impl Default for Solver {
-    fn default() -> Self {
-        Self {
-            steps: vec![],
-            current_step: None,
-        }
+/// no step of a [`Solver`] makes progress, but the grid isn't solved yet
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Stuck;
+
+impl fmt::Display for Stuck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("no step makes progress")
     }
 }
 
+impl std::error::Error for Stuck {}
+
+/// a step that made progress, as reported by [`Solver::run`]
+pub struct Applied<'a> {
+    /// the position of the step in the order it was added
+    pub index: usize,
+    pub step: &'a dyn SolverStep,
+    pub before: &'a Candidates,
+    pub after: &'a Candidates,
+}
+
+#[derive(Debug, Default)]
+pub struct Solver {
+    steps: Vec<Box<dyn SolverStep>>,
+    strategy: Strategy,
+    /// the step that was applied last, which is where [`Strategy::Fixpoint`] continues
+    current_step: Option<usize>,
+    assume_uniqueness: bool,
+}
+
 impl Solver {
     pub fn add_step<S: SolverStep + 'static>(&mut self, step: S) {
         self.steps.push(Box::new(step));
@@ -30,31 +59,516 @@ impl Solver {
         self.steps.len()
     }
 
-    pub fn next_step(&mut self, game: Game) -> Game {
+    pub fn strategy(&self) -> Strategy {
+        self.strategy
+    }
+
+    pub fn set_strategy(&mut self, strategy: Strategy) {
+        self.strategy = strategy;
+        self.current_step = None;
+    }
+
+    pub fn assumes_uniqueness(&self) -> bool {
+        self.assume_uniqueness
+    }
+
+    /// whether the steps that rely on the puzzle having a unique solution may be used, which
+    /// they aren't by default
+    pub fn set_assume_uniqueness(&mut self, assume_uniqueness: bool) {
+        self.assume_uniqueness = assume_uniqueness;
+    }
+
+    /// applies the steps once according to the strategy, returns whether any made progress
+    fn round(&mut self, candidates: &mut Candidates, applied: &mut dyn FnMut(Applied)) -> bool {
+        let mut progress = false;
+        let assume_uniqueness = self.assume_uniqueness;
+        let mut apply = |index: usize, step: &dyn SolverStep, candidates: &mut Candidates| {
+            let before = *candidates;
+            if (step.needs_uniqueness() && !assume_uniqueness) || !step.apply_candidates(candidates)
+            {
+                return false;
+            }
+
+            info!("applied step {}: {}", index, step.name());
+            applied(Applied {
+                index,
+                step,
+                before: &before,
+                after: candidates,
+            });
+            true
+        };
+
+        match self.strategy {
+            Strategy::SimplestFirst => {
+                for (index, step) in self.steps.iter().enumerate() {
+                    if apply(index, step.as_ref(), candidates) {
+                        self.current_step = Some(index);
+                        return true;
+                    }
+                }
+            }
+            Strategy::Fixpoint => {
+                let first = self.current_step.map_or(0, |step| step + 1);
+                for offset in 0..self.steps.len() {
+                    let index = (first + offset) % self.steps.len();
+                    while !candidates.is_solved()
+                        && apply(index, self.steps[index].as_ref(), candidates)
+                    {
+                        progress = true;
+                    }
+
+                    if progress {
+                        self.current_step = Some(index);
+                        return true;
+                    }
+                }
+            }
+            Strategy::Exhaustive => {
+                for (index, step) in self.steps.iter().enumerate() {
+                    if apply(index, step.as_ref(), candidates) {
+                        self.current_step = Some(index);
+                        progress = true;
+                    }
+                }
+            }
+        }
+
+        progress
+    }
+
+    /// runs the steps until the candidates are solved, or no step makes progress anymore.
+    /// `applied` is called for every application of a step that made progress.
+    pub fn run(
+        &mut self,
+        candidates: &mut Candidates,
+        mut applied: impl FnMut(Applied),
+    ) -> Result<(), Stuck> {
+        while !candidates.is_solved() {
+            if !self.round(candidates, &mut applied) {
+                return Err(Stuck);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// applies the steps once according to the strategy.
+    ///
+    /// a game without any notes gets its notes generated first, which counts as a step of its
+    /// own. a solved game is returned as it is.
+    pub fn next_step(&mut self, game: Game) -> Result<Game, Stuck> {
         info!(
-            "running next step in solver with {} steps",
-            self.count_steps()
+            "running next step in solver with {} steps, {:?}",
+            self.count_steps(),
+            self.strategy
         );
-        if self.count_steps() == 0 {
-            return game;
+        if game.is_solved() {
+            return Ok(game);
+        }
+
+        let mut candidates = starting_candidates(&game);
+        let marked = candidates.apply_to(game);
+        if marked != game {
+            return Ok(marked);
         }
 
-        let next_step = if let Some(current_step) = self.current_step {
-            current_step + 1
+        if self.round(&mut candidates, &mut |_| {}) {
+            Ok(candidates.apply_to(game))
         } else {
-            0
+            Err(Stuck)
+        }
+    }
+
+    /// solves the game as far as the steps get and records every step on the way.
+    ///
+    /// the notes of the game are taken as the starting candidates. a game without any notes
+    /// starts with every candidate that isn't ruled out by a placed digit.
+    pub fn solve_log(&mut self, game: Game) -> SolveLog {
+        let mut candidates = starting_candidates(&game);
+        let mut log = SolveLog {
+            start: candidates.apply_to(game),
+            entries: vec![],
         };
 
-        let next_step = next_step % self.count_steps();
-        info!("next step: {}: {:?}", next_step, self.steps[next_step]);
+        // getting stuck just ends the log early
+        let _ = self.run(&mut candidates, |applied| {
+            let (placements, eliminations) = changes(applied.before, applied.after);
+            let mut entry = LogEntry {
+                technique: applied.step.name().to_string(),
+                placements,
+                eliminations,
+                explanation: String::new(),
+                chain: applied.step.chain(applied.before),
+                branches: applied.step.branches(applied.before),
+                pattern: applied.step.pattern(applied.before),
+            };
+            entry.explanation = applied.step.explain(&entry);
+            log.entries.push(entry);
+        });
+
+        log
+    }
+}
 
-        self.current_step = Some(next_step);
-        self.steps[next_step].apply(game)
+/// the notes of the game, or if it has none, every candidate not ruled out by a placed digit
+fn starting_candidates(game: &Game) -> Candidates {
+    let has_notes = (0..81).any(|idx| {
+        let (row, col) = Game::cell_index_to_coords(idx);
+        game.get_notes(row, col) != 0
+    });
+
+    if has_notes {
+        Candidates::from_game(game)
+    } else {
+        Candidates::from_numbers(&game.current_numbers())
     }
 }
 
+/// a digit in a cell, identified by its cell index
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Candidate {
+    pub cell: usize,
+    pub digit: u8,
+}
+
+impl Candidate {
+    /// the cell in the usual `r1c1` notation
+    pub fn cell_name(&self) -> String {
+        cells_name(&[self.cell])
+    }
+}
+
+/// names a group of cells in `r1c1` notation, compressed to `r1c23` for cells in one row and
+/// to `r12c3` for cells in one column
+pub fn cells_name(cells: &[usize]) -> String {
+    let rows: Vec<usize> = cells.iter().map(|cell| cell / 9 + 1).collect();
+    let cols: Vec<usize> = cells.iter().map(|cell| cell % 9 + 1).collect();
+    let join = |values: &[usize]| values.iter().map(|v| v.to_string()).collect::<String>();
+
+    if rows.iter().all(|&row| row == rows[0]) {
+        format!("r{}c{}", rows[0], join(&cols))
+    } else if cols.iter().all(|&col| col == cols[0]) {
+        format!("r{}c{}", join(&rows), cols[0])
+    } else {
+        let names: Vec<String> = cells.iter().map(|&cell| cells_name(&[cell])).collect();
+        names.join("|")
+    }
+}
+
+/// how two neighbouring nodes of a [`Chain`] depend on each other
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Link {
+    /// at least one of the nodes is true
+    Strong,
+    /// at most one of the nodes is true
+    Weak,
+}
+
+/// a node of a [`Chain`]: a digit in one cell, or in a group of cells of which at most one can
+/// hold it
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChainNode {
+    pub digit: u8,
+    pub cells: Vec<usize>,
+}
+
+impl ChainNode {
+    pub fn cell(cell: usize, digit: u8) -> Self {
+        ChainNode {
+            digit,
+            cells: vec![cell],
+        }
+    }
+
+    pub fn group(cells: Bitboard, digit: u8) -> Self {
+        ChainNode {
+            digit,
+            cells: cells.iter().collect(),
+        }
+    }
+}
+
+/// the chain of inferences behind a step, as reported for display
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Chain {
+    pub nodes: Vec<ChainNode>,
+    /// the links between neighbouring nodes, one less than there are nodes
+    pub links: Vec<Link>,
+}
+
+impl Chain {
+    /// a chain whose links alternate between strong and weak, starting with a strong one
+    pub fn alternating(nodes: Vec<ChainNode>) -> Self {
+        let links = (1..nodes.len())
+            .map(|i| if i % 2 == 1 { Link::Strong } else { Link::Weak })
+            .collect();
+        Chain { nodes, links }
+    }
+}
+
+/// writes the chain in eureka notation, like `(5)r1c2=r1c7-r4c7=r4c3`, where `=` is a strong
+/// and `-` a weak link. the digit is only written where it changes.
+impl fmt::Display for Chain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut digit = None;
+        for (i, node) in self.nodes.iter().enumerate() {
+            if i > 0 {
+                f.write_str(match self.links[i - 1] {
+                    Link::Strong => "=",
+                    Link::Weak => "-",
+                })?;
+            }
+            if digit != Some(node.digit) {
+                write!(f, "({})", node.digit)?;
+                digit = Some(node.digit);
+            }
+            f.write_str(&cells_name(&node.cells))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// a group of cells that plays one role in the pattern behind a step, like the pivot of a wing
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PatternPart {
+    /// what the cells are to the pattern, like "pivot" or "pincer"
+    pub role: String,
+    pub cells: Vec<usize>,
+    /// the candidates of the cells that take part in the pattern
+    pub digits: Vec<u8>,
+}
+
+impl PatternPart {
+    /// a part made of the given cells and the digits of the `notes` (in the layout of the notes
+    /// of a [`Game`])
+    pub fn new(role: &str, cells: Bitboard, notes: u16) -> Self {
+        PatternPart {
+            role: role.to_string(),
+            cells: cells.iter().collect(),
+            digits: (1..=9).filter(|d| notes & (1 << (d - 1)) != 0).collect(),
+        }
+    }
+}
+
+impl fmt::Display for PatternPart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {{", self.role, cells_name(&self.cells))?;
+        for digit in &self.digits {
+            write!(f, "{digit}")?;
+        }
+        f.write_str("}")
+    }
+}
+
+/// one step of a [`SolveLog`]
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LogEntry {
+    /// the name of the [`SolverStep`] that was applied
+    pub technique: String,
+    pub placements: Vec<Candidate>,
+    /// the candidates that were removed, not counting those removed by the placements
+    pub eliminations: Vec<Candidate>,
+    pub explanation: String,
+    /// the chain behind the step, for the techniques that use one
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub chain: Option<Chain>,
+    /// the chains that each start from one of the possibilities of a forcing chain and end in
+    /// the same conclusion
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub branches: Vec<Chain>,
+    /// the named parts of the pattern behind the step, for the techniques that report them
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub pattern: Vec<PatternPart>,
+}
+
+/// the ordered steps that solve a game, as created by [`Solver::solve_log`]
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SolveLog {
+    /// the game with the starting candidates as notes
+    pub start: Game,
+    pub entries: Vec<LogEntry>,
+}
+
+impl SolveLog {
+    /// the game after the first `steps` entries were applied
+    pub fn game_at(&self, steps: usize) -> Game {
+        let mut candidates = Candidates::from_game(&self.start);
+        for entry in &self.entries[..steps.min(self.entries.len())] {
+            for placement in &entry.placements {
+                candidates.place(placement.cell, placement.digit);
+            }
+            for elimination in &entry.eliminations {
+                candidates.eliminate(elimination.digit, Bitboard::cell(elimination.cell));
+            }
+        }
+
+        candidates.apply_to(self.start)
+    }
+
+    /// whether the steps solve the game, or the solver got stuck
+    pub fn is_solved(&self) -> bool {
+        self.game_at(self.entries.len()).is_solved()
+    }
+}
+
+/// the placements and the eliminations between two states, where eliminations that follow
+/// from the placements are left out
+fn changes(before: &Candidates, after: &Candidates) -> (Vec<Candidate>, Vec<Candidate>) {
+    let mut placements = vec![];
+    let mut implied = [Bitboard::EMPTY; 9];
+    for cell in before.unsolved() & !after.unsolved() {
+        let digit = after.value(cell);
+        placements.push(Candidate { cell, digit });
+        implied[digit as usize - 1] |= PEERS[cell];
+        for implied in implied.iter_mut() {
+            implied.insert(cell);
+        }
+    }
+
+    let mut eliminations = vec![];
+    for digit in 1..=9u8 {
+        let removed = before.digit(digit) & !after.digit(digit) & !implied[digit as usize - 1];
+        eliminations.extend(removed.iter().map(|cell| Candidate { cell, digit }));
+    }
+    eliminations.sort_by_key(|candidate| (candidate.cell, candidate.digit));
+
+    (placements, eliminations)
+}
+
 pub trait SolverStep: Debug {
-    fn apply(&self, state: Game) -> Game;
+    /// the name of the technique, as shown in the solve log
+    fn name(&self) -> &'static str;
+
+    /// runs the step on the candidates of a grid, returns whether it made any progress
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool;
+
+    /// whether the step relies on the puzzle having a unique solution, so a [`Solver`] only
+    /// uses it when told to assume that
+    fn needs_uniqueness(&self) -> bool {
+        false
+    }
+
+    /// the chain the step would use on these candidates, for the techniques that use one
+    fn chain(&self, _candidates: &Candidates) -> Option<Chain> {
+        None
+    }
+
+    /// the branches of a forcing chain the step would use on these candidates
+    fn branches(&self, _candidates: &Candidates) -> Vec<Chain> {
+        vec![]
+    }
+
+    /// the parts of the pattern the step would use on these candidates, for the techniques that
+    /// name them
+    fn pattern(&self, _candidates: &Candidates) -> Vec<PatternPart> {
+        vec![]
+    }
+
+    /// describes what an application of the step did, for the solve log, from the entry it
+    /// made (whose explanation is still empty)
+    fn explain(&self, entry: &LogEntry) -> String {
+        let mut parts = vec![];
+        if let Some(chain) = &entry.chain {
+            parts.push(chain.to_string());
+        }
+        if !entry.pattern.is_empty() {
+            let roles: Vec<String> = entry.pattern.iter().map(|part| part.to_string()).collect();
+            parts.push(roles.join(", "));
+        }
+        if !entry.branches.is_empty() {
+            let branches: Vec<String> = entry
+                .branches
+                .iter()
+                .map(|chain| chain.to_string())
+                .collect();
+            parts.push(branches.join(" | "));
+        }
+        if !entry.placements.is_empty() {
+            let cells: Vec<String> = entry
+                .placements
+                .iter()
+                .map(|p| format!("{}={}", p.cell_name(), p.digit))
+                .collect();
+            parts.push(format!("places {}", cells.join(", ")));
+        }
+        if !entry.eliminations.is_empty() {
+            let cells: Vec<String> = entry
+                .eliminations
+                .iter()
+                .map(|e| format!("{}<>{}", e.cell_name(), e.digit))
+                .collect();
+            parts.push(format!("eliminates {}", cells.join(", ")));
+        }
+
+        format!("{}: {}", self.name(), parts.join("; "))
+    }
+
+    /// runs the step on the notes of a game
+    fn apply(&self, state: Game) -> Game {
+        let mut candidates = Candidates::from_game(&state);
+        if self.apply_candidates(&mut candidates) {
+            candidates.apply_to(state)
+        } else {
+            state
+        }
+    }
+}
+
+/// lets borrowed steps, like the ones of [`crate::rating::techniques`], be added to a solver
+impl<S: SolverStep + ?Sized> SolverStep for &S {
+    fn name(&self) -> &'static str {
+        (**self).name()
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        (**self).apply_candidates(candidates)
+    }
+
+    fn needs_uniqueness(&self) -> bool {
+        (**self).needs_uniqueness()
+    }
+
+    fn chain(&self, candidates: &Candidates) -> Option<Chain> {
+        (**self).chain(candidates)
+    }
+
+    fn branches(&self, candidates: &Candidates) -> Vec<Chain> {
+        (**self).branches(candidates)
+    }
+
+    fn pattern(&self, candidates: &Candidates) -> Vec<PatternPart> {
+        (**self).pattern(candidates)
+    }
+
+    fn explain(&self, entry: &LogEntry) -> String {
+        (**self).explain(entry)
+    }
+
+    fn apply(&self, state: Game) -> Game {
+        (**self).apply(state)
+    }
 }
 
 /// a solver step that reduces the "possible values" of each cell by eliminating every value
@@ -63,41 +577,22 @@ pub trait SolverStep: Debug {
 pub struct GenerateBasicMarkingsStep;
 
 impl SolverStep for GenerateBasicMarkingsStep {
-    fn apply(&self, state: Game) -> Game {
-        let mut seen_numbers = [0u16; 27];
-        /** 27 = 9 rows + 9 cols + 9 groups */
-        for row in 0..9 {
-            for cell in 0..9 {
-                let value = state.get(row + 1, cell + 1);
-                if value > 0 {
-                    info!("add seen digit: {}: {}", value, 1u16 << (value - 1));
-                    let box_id = (row / 3) * 3 + (cell / 3);
-                    seen_numbers[row] += 1u16 << (value - 1) as i16;
-                    seen_numbers[9 + cell] += 1u16 << (value - 1) as i16;
-                    seen_numbers[18 + box_id] += 1u16 << (value - 1) as i16;
-                }
-            }
+    fn name(&self) -> &'static str {
+        "Basic Markings"
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        let mut changed = false;
+        for idx in !candidates.unsolved() {
+            changed |= candidates.eliminate(candidates.value(idx), PEERS[idx]);
         }
 
-        let mut new_game = state;
-        for idx in 0..81 {
-            let (row, col) = sudoku::Game::cell_index_to_coords(idx);
-            let value = state.get(row, col);
+        changed
+    }
 
-            if value == 0 {
-                let box_id = ((row - 1) / 3) * 3 + ((col - 1) / 3);
-                //TODO: use the existing note, if set, instead of always assuming 0x01FF?
-                new_game.set_notes(
-                    row,
-                    col,
-                    0x01FF
-                        & !seen_numbers[row - 1]
-                        & !seen_numbers[9 + col - 1]
-                        & !seen_numbers[18 + box_id],
-                )
-            }
-        }
-        new_game
+    fn apply(&self, state: Game) -> Game {
+        //TODO: use the existing note, if set, instead of always assuming 0x01FF?
+        Candidates::from_numbers(&state.current_numbers()).apply_to(state)
     }
 }
 
@@ -106,48 +601,222 @@ impl SolverStep for GenerateBasicMarkingsStep {
 pub struct NakedSingleStep;
 
 impl SolverStep for NakedSingleStep {
-    fn apply(&self, state: Game) -> Game {
-        let mut new_game = state;
+    fn name(&self) -> &'static str {
+        "Naked Single"
+    }
 
-        for idx in 0..81 {
-            let (row, col) = sudoku::Game::cell_index_to_coords(idx);
-            let value = state.get(row, col);
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        let singles = candidates.naked_singles();
+        for idx in singles {
+            let notes = candidates.cell(idx);
+            // an earlier single in the same unit may have taken the last candidate
+            if notes != 0 {
+                candidates.place(idx, notes.trailing_zeros() as u8 + 1);
+            }
+        }
 
-            if value == 0 {
-                let notes = state.get_notes(row, col);
-                match notes {
-                    0x0001 => {
-                        new_game.set(row, col, 1);
-                    }
-                    0x0002 => {
-                        new_game.set(row, col, 2);
-                    }
-                    0x0004 => {
-                        new_game.set(row, col, 3);
-                    }
-                    0x0008 => {
-                        new_game.set(row, col, 4);
-                    }
-                    0x0010 => {
-                        new_game.set(row, col, 5);
-                    }
-                    0x0020 => {
-                        new_game.set(row, col, 6);
-                    }
-                    0x0040 => {
-                        new_game.set(row, col, 7);
-                    }
-                    0x0080 => {
-                        new_game.set(row, col, 8);
-                    }
-                    0x0100 => {
-                        new_game.set(row, col, 9);
+        !singles.is_empty()
+    }
+}
+
+/// if a digit is only noted in a single cell of a unit, it has to go there
+#[derive(Debug)]
+pub struct HiddenSingleStep;
+
+impl SolverStep for HiddenSingleStep {
+    fn name(&self) -> &'static str {
+        "Hidden Single"
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        let mut changed = false;
+        for house in HOUSES {
+            for digit in 1..=9u8 {
+                let cells = candidates.digit(digit) & house;
+                if cells.count() == 1 {
+                    candidates.place(cells.first().unwrap(), digit);
+                    changed = true;
+                }
+            }
+        }
+
+        changed
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use proptest::prelude::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::Strategy;
+    use super::*;
+    use crate::brute_force;
+    use crate::rating;
+    use crate::sudoku::Numbers;
+
+    /// a puzzle made from a random solution by removing the cells in `removed`
+    fn puzzle(seed: u64, removed: &[bool]) -> (Numbers, Numbers) {
+        let solution = brute_force::random_solution(&mut StdRng::seed_from_u64(seed));
+        let mut cells: [u8; 81] = solution.into();
+        for (cell, &remove) in cells.iter_mut().zip(removed) {
+            if remove {
+                *cell = 0;
+            }
+        }
+
+        (Numbers::from(cells), solution)
+    }
+
+    /// the steps that hold for any puzzle, as the random ones may have several solutions
+    fn steps() -> Vec<&'static dyn SolverStep> {
+        let mut steps: Vec<&'static dyn SolverStep> = vec![&GenerateBasicMarkingsStep];
+        steps.extend(
+            rating::techniques()
+                .into_iter()
+                .map(|(step, _)| step)
+                .filter(|step| !step.needs_uniqueness()),
+        );
+        steps
+    }
+
+    #[test]
+    fn solve_log_replays_to_the_solution() {
+        let puzzle: Numbers = PUZZLE.parse().unwrap();
+        let mut solver = Solver::default();
+        solver.add_step(NakedSingleStep);
+        solver.add_step(HiddenSingleStep);
+
+        let log = solver.solve_log(Game::create(puzzle));
+        assert!(log.is_solved());
+        assert_eq!(
+            Candidates::from_numbers(&puzzle).apply_to(Game::create(puzzle)),
+            log.game_at(0)
+        );
+        assert_eq!("Naked Single", log.entries[0].technique);
+        assert!(log.entries[0]
+            .explanation
+            .starts_with("Naked Single: places r"));
+
+        // every entry places at least one digit, and replaying keeps the game valid
+        for steps in 1..=log.entries.len() {
+            assert!(!log.entries[steps - 1].placements.is_empty());
+            assert!(log.game_at(steps).is_valid());
+        }
+    }
+
+    const PUZZLE: &str =
+        "1...6....98....6.5.....5..1......3.4.6.13.9...4.72.....93.761....648...75..9.246.";
+
+    /// the indices of the steps in the order a solver with `strategy` applied them, together
+    /// with the candidates after each application
+    fn applications(strategy: Strategy) -> Vec<(usize, Candidates)> {
+        let mut solver = Solver::default();
+        solver.set_strategy(strategy);
+        solver.add_step(NakedSingleStep);
+        solver.add_step(HiddenSingleStep);
+
+        let mut applied = vec![];
+        let mut candidates = Candidates::from_numbers(&PUZZLE.parse().unwrap());
+        solver
+            .run(&mut candidates, |step| {
+                applied.push((step.index, *step.after))
+            })
+            .unwrap();
+        assert!(candidates.is_solved());
+        applied
+    }
+
+    #[test]
+    fn simplest_first_only_falls_back_when_simpler_steps_are_stuck() {
+        let applied = applications(Strategy::SimplestFirst);
+        for window in applied.windows(2) {
+            if window[1].0 == 1 {
+                let mut candidates = window[0].1;
+                assert!(!NakedSingleStep.apply_candidates(&mut candidates));
+            }
+        }
+    }
+
+    #[test]
+    fn fixpoint_exhausts_a_step_before_moving_on() {
+        let applied = applications(Strategy::Fixpoint);
+        for window in applied.windows(2) {
+            if window[0].0 != window[1].0 {
+                let mut candidates = window[0].1;
+                let steps = [&NakedSingleStep as &dyn SolverStep, &HiddenSingleStep];
+                assert!(
+                    candidates.is_solved() || !steps[window[0].0].apply_candidates(&mut candidates)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn exhaustive_applies_every_step_per_round() {
+        let applied = applications(Strategy::Exhaustive);
+        assert!(applied.iter().any(|(index, _)| *index == 1));
+        assert!(applied.len() <= applications(Strategy::SimplestFirst).len());
+    }
+
+    #[test]
+    fn reports_stuck_instead_of_looping() {
+        let mut solver = Solver::default();
+        solver.add_step(NakedSingleStep);
+
+        let game = Game::create(Numbers::empty());
+        let marked = solver.next_step(game).unwrap();
+        assert_ne!(game, marked);
+        assert_eq!(Err(Stuck), solver.next_step(marked));
+
+        let solved = Game::create(match brute_force::solve(&PUZZLE.parse().unwrap()) {
+            brute_force::Solutions::Unique(solution) => solution,
+            _ => unreachable!(),
+        });
+        assert_eq!(Ok(solved), solver.next_step(solved));
+    }
+
+    #[test]
+    fn solve_log_stops_when_stuck() {
+        let log = Solver::default().solve_log(Game::create(Numbers::empty()));
+        assert!(log.entries.is_empty());
+        assert!(!log.is_solved());
+    }
+
+    proptest! {
+        #[test]
+        fn steps_never_break_the_solution(
+            seed in any::<u64>(),
+            removed in prop::collection::vec(any::<bool>(), 81),
+        ) {
+            let (puzzle, solution) = puzzle(seed, &removed);
+            let mut candidates = Candidates::from_numbers(&puzzle);
+
+            // keep applying steps until none of them makes progress anymore
+            while steps().iter().any(|step| step.apply_candidates(&mut candidates)) {
+                for idx in 0..81 {
+                    let expected = solution.get_by_offset(idx);
+                    match candidates.value(idx) {
+                        0 => prop_assert!(candidates.cell(idx) & (1 << (expected - 1)) != 0),
+                        value => prop_assert_eq!(expected, value),
                     }
-                    _ => {}
-                };
+                }
             }
         }
 
-        new_game
+        #[test]
+        fn steps_keep_games_valid(
+            seed in any::<u64>(),
+            removed in prop::collection::vec(any::<bool>(), 81),
+        ) {
+            let (puzzle, _) = puzzle(seed, &removed);
+            let mut game = GenerateBasicMarkingsStep.apply(Game::create(puzzle));
+
+            for step in steps() {
+                game = step.apply(game);
+                prop_assert!(game.is_valid(), "{:?} made the game invalid", step);
+            }
+        }
     }
 }
@@ -1,4 +1,6 @@
-use std::ops::{BitXorAssign, Shr};
+use std::fmt;
+use std::ops::BitXorAssign;
+use std::str::FromStr;
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct Numbers([u8; 81]);
@@ -9,12 +11,189 @@ impl From<[u8; 81]> for Numbers {
     }
 }
 
+impl From<Numbers> for [u8; 81] {
+    fn from(value: Numbers) -> Self {
+        value.0
+    }
+}
+
+/// reasons why a line could not be parsed into [`Numbers`]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ParseNumbersError {
+    /// the line does not contain exactly 81 cells
+    InvalidLength(usize),
+    /// the character at the given offset is neither a digit nor an empty-cell marker
+    InvalidCharacter(usize, char),
+}
+
+impl fmt::Display for ParseNumbersError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseNumbersError::InvalidLength(len) => {
+                write!(f, "expected 81 cells, found {}", len)
+            }
+            ParseNumbersError::InvalidCharacter(offset, c) => {
+                write!(f, "invalid character {:?} at cell {}", c, offset + 1)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseNumbersError {}
+
+/// a value that is outside of the range the grid allows
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RangeError {
+    Row(usize),
+    Col(usize),
+    CellIndex(usize),
+    Digit(usize),
+    Notes(u16),
+}
+
+impl fmt::Display for RangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RangeError::Row(row) => write!(f, "row {} is not in 1-9", row),
+            RangeError::Col(col) => write!(f, "col {} is not in 1-9", col),
+            RangeError::CellIndex(index) => write!(f, "cell index {} is not in 0-80", index),
+            RangeError::Digit(digit) => write!(f, "digit {} is not in 1-9", digit),
+            RangeError::Notes(notes) => write!(f, "notes {:#06x} have bits above 0x01FF", notes),
+        }
+    }
+}
+
+impl std::error::Error for RangeError {}
+
+/// a row of the grid, 1 (top) to 9 (bottom)
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub struct Row(u8);
+
+impl Row {
+    pub fn new(row: usize) -> Result<Self, RangeError> {
+        match row {
+            1..=9 => Ok(Row(row as u8)),
+            _ => Err(RangeError::Row(row)),
+        }
+    }
+
+    pub fn get(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// a column of the grid, 1 (left) to 9 (right)
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub struct Col(u8);
+
+impl Col {
+    pub fn new(col: usize) -> Result<Self, RangeError> {
+        match col {
+            1..=9 => Ok(Col(col as u8)),
+            _ => Err(RangeError::Col(col)),
+        }
+    }
+
+    pub fn get(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// a digit that can be placed in a cell, 1 to 9
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub struct Digit(u8);
+
+impl Digit {
+    pub fn new(digit: u8) -> Result<Self, RangeError> {
+        match digit {
+            1..=9 => Ok(Digit(digit)),
+            _ => Err(RangeError::Digit(digit as usize)),
+        }
+    }
+
+    pub fn get(self) -> u8 {
+        self.0
+    }
+
+    /// the bit of this digit in a notes mask
+    pub fn mask(self) -> u16 {
+        1 << (self.0 - 1)
+    }
+}
+
+/// the offset of a cell in the grid, counted row by row from 0 to 80
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub struct CellIndex(u8);
+
+impl CellIndex {
+    pub fn new(index: usize) -> Result<Self, RangeError> {
+        match index {
+            0..=80 => Ok(CellIndex(index as u8)),
+            _ => Err(RangeError::CellIndex(index)),
+        }
+    }
+
+    pub fn from_coords(row: Row, col: Col) -> Self {
+        CellIndex(((row.0 - 1) * 9) + (col.0 - 1))
+    }
+
+    pub fn get(self) -> usize {
+        self.0 as usize
+    }
+
+    pub fn row(self) -> Row {
+        Row(self.0 / 9 + 1)
+    }
+
+    pub fn col(self) -> Col {
+        Col(self.0 % 9 + 1)
+    }
+}
+
+/// parses the common "line format": 81 characters, row by row, where `1`-`9` are
+/// filled cells and `0` or `.` mark an empty cell.
+impl FromStr for Numbers {
+    type Err = ParseNumbersError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let len = s.chars().count();
+        if len != 81 {
+            return Err(ParseNumbersError::InvalidLength(len));
+        }
+
+        let mut numbers = Numbers::empty();
+        for (offset, c) in s.chars().enumerate() {
+            numbers.0[offset] = match c {
+                '.' | '0' => 0,
+                '1'..='9' => c as u8 - b'0',
+                _ => return Err(ParseNumbersError::InvalidCharacter(offset, c)),
+            };
+        }
+
+        Ok(numbers)
+    }
+}
+
+/// writes the numbers in the line format, using `.` for empty cells
+impl fmt::Display for Numbers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for value in self.cells() {
+            match value {
+                0 => f.write_str(".")?,
+                x => write!(f, "{}", x)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl Numbers {
-    fn empty() -> Self {
+    pub fn empty() -> Self {
         Numbers([0u8; 81])
     }
 
-    fn cells(&self) -> Cells {
+    pub fn cells(&self) -> Cells<'_> {
         Cells {
             current_index: 0,
             values: &self.0,
@@ -36,7 +215,16 @@ impl Numbers {
         self.0[index]
     }
 
-    fn get_by_offset(&self, offset: usize) -> u8 {
+    /// the digit in a cell, or `None` if it is empty
+    pub fn value(&self, cell: CellIndex) -> Option<Digit> {
+        Digit::new(self.0[cell.get()]).ok()
+    }
+
+    pub fn set_value(&mut self, cell: CellIndex, value: Option<Digit>) {
+        self.0[cell.get()] = value.map_or(0, Digit::get);
+    }
+
+    pub fn get_by_offset(&self, offset: usize) -> u8 {
         assert!(offset < 81);
 
         self.0[offset]
@@ -53,8 +241,8 @@ impl Numbers {
         assert!((1..=9).contains(&col));
         let mut buffer = [0u8; 9];
         let offset = col - 1;
-        for row in 0..9 {
-            buffer[row] = self.0[(row * 9) + offset];
+        for (row, value) in buffer.iter_mut().enumerate() {
+            *value = self.0[(row * 9) + offset];
         }
 
         buffer
@@ -96,7 +284,7 @@ pub struct Cells<'a> {
     values: &'a [u8],
 }
 
-impl<'a> Iterator for Cells<'a> {
+impl Iterator for Cells<'_> {
     type Item = u8;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -128,21 +316,31 @@ impl Game {
         }
     }
 
+    pub fn given_numbers(&self) -> Numbers {
+        self.given_numbers
+    }
+
+    pub fn current_numbers(&self) -> Numbers {
+        self.current_numbers
+    }
+
     pub fn get_notes(&self, row: usize, col: usize) -> u16 {
         self.notes[Self::coords_to_cell_index(row, col)]
     }
 
     pub fn set_notes(&mut self, row: usize, col: usize, notes: u16) {
-        debug_assert!(row <= 9 && row >= 1);
-        debug_assert!(col <= 9 && col >= 1);
+        debug_assert!((1..=9).contains(&row));
+        debug_assert!((1..=9).contains(&col));
         debug_assert!(notes <= 0x01FF);
 
         self.notes[Self::coords_to_cell_index(row, col)] = notes;
     }
 
+    /// toggles a single note (1-9) of a cell. other values are not a note and do nothing.
     pub fn toggle_note(&mut self, row: usize, col: usize, note: u8) {
-        self.notes[Self::coords_to_cell_index(row, col)]
-            .bitxor_assign(1u16 << (note - 1).max(0) as i16);
+        if let Ok(digit) = Digit::new(note) {
+            self.notes[Self::coords_to_cell_index(row, col)].bitxor_assign(digit.mask());
+        }
     }
 
     pub fn cell_index_to_coords(index: usize) -> (usize, usize) {
@@ -158,7 +356,7 @@ impl Game {
         ((row - 1) * 9) + (col - 1)
     }
 
-    pub fn cells(&self) -> Cells {
+    pub fn cells(&self) -> Cells<'_> {
         self.current_numbers.cells()
     }
 
@@ -194,6 +392,11 @@ impl Game {
                     continue;
                 }
 
+                // numbers created from raw arrays may contain anything
+                if num > 9 {
+                    return false;
+                }
+
                 let idx = (num - 1) as usize;
                 if seen[idx] {
                     return false;
@@ -202,7 +405,7 @@ impl Game {
                 seen[idx] = true;
             }
 
-            return true;
+            true
         }
 
         // lets first get a naive impl right before trying to be smart
@@ -225,9 +428,195 @@ impl Game {
         }
         true
     }
+
+    /// the digit in a cell, or `None` if it is empty
+    pub fn value(&self, cell: CellIndex) -> Option<Digit> {
+        self.current_numbers.value(cell)
+    }
+
+    /// sets or clears a cell, returns false if the cell is given and can't be changed
+    pub fn set_value(&mut self, cell: CellIndex, value: Option<Digit>) -> bool {
+        if self.given_numbers.value(cell).is_some() {
+            return false;
+        }
+
+        self.current_numbers.set_value(cell, value);
+        true
+    }
+
+    pub fn is_given_cell(&self, cell: CellIndex) -> bool {
+        self.given_numbers.value(cell).is_some()
+    }
+
+    pub fn notes(&self, cell: CellIndex) -> u16 {
+        self.notes[cell.get()]
+    }
+
+    pub fn toggle_digit_note(&mut self, cell: CellIndex, digit: Digit) {
+        self.notes[cell.get()] ^= digit.mask();
+    }
+
+    /// like [`Game::get`], but returns an error instead of panicking on bad coordinates
+    pub fn try_get(&self, row: usize, col: usize) -> Result<u8, RangeError> {
+        let cell = CellIndex::from_coords(Row::new(row)?, Col::new(col)?);
+        Ok(self.value(cell).map_or(0, Digit::get))
+    }
+
+    /// like [`Game::set`], but returns an error instead of panicking on bad input.
+    /// a value of 0 clears the cell.
+    pub fn try_set(&mut self, row: usize, col: usize, value: u8) -> Result<bool, RangeError> {
+        let cell = CellIndex::from_coords(Row::new(row)?, Col::new(col)?);
+        let value = match value {
+            0 => None,
+            x => Some(Digit::new(x)?),
+        };
+
+        Ok(self.set_value(cell, value))
+    }
+
+    /// like [`Game::get_notes`], but returns an error instead of panicking on bad coordinates
+    pub fn try_get_notes(&self, row: usize, col: usize) -> Result<u16, RangeError> {
+        let cell = CellIndex::from_coords(Row::new(row)?, Col::new(col)?);
+        Ok(self.notes(cell))
+    }
+
+    /// like [`Game::set_notes`], but returns an error instead of panicking on bad input
+    pub fn try_set_notes(&mut self, row: usize, col: usize, notes: u16) -> Result<(), RangeError> {
+        let cell = CellIndex::from_coords(Row::new(row)?, Col::new(col)?);
+        if notes > 0x01FF {
+            return Err(RangeError::Notes(notes));
+        }
+
+        self.notes[cell.get()] = notes;
+        Ok(())
+    }
+
+    /// like [`Game::toggle_note`], but returns an error for bad coordinates or notes
+    pub fn try_toggle_note(&mut self, row: usize, col: usize, note: u8) -> Result<(), RangeError> {
+        let cell = CellIndex::from_coords(Row::new(row)?, Col::new(col)?);
+        self.toggle_digit_note(cell, Digit::new(note)?);
+        Ok(())
+    }
+
+    /// like [`Game::cell_index_to_coords`], but returns an error for indices past the grid
+    pub fn try_cell_index_to_coords(index: usize) -> Result<(usize, usize), RangeError> {
+        let cell = CellIndex::new(index)?;
+        Ok((cell.row().get(), cell.col().get()))
+    }
+
+    /// like [`Game::coords_to_cell_index`], but returns an error for bad coordinates
+    pub fn try_coords_to_cell_index(row: usize, col: usize) -> Result<usize, RangeError> {
+        Ok(CellIndex::from_coords(Row::new(row)?, Col::new(col)?).get())
+    }
+
+    /// a game is solved when every cell is filled and no unit contains a duplicate
+    pub fn is_solved(&self) -> bool {
+        self.cells().all(|value| value != 0) && self.is_valid()
+    }
+}
+
+/// reasons why a string could not be parsed into a [`Game`]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ParseGameError {
+    /// the string does not consist of two or three `:` separated parts
+    InvalidFormat,
+    Givens(ParseNumbersError),
+    Entries(ParseNumbersError),
+    /// the notes at the given cell are not a hex number up to `1ff`
+    InvalidNotes(usize),
+    /// an entry was made on a cell that is already given
+    EntryOnGiven(usize),
+}
+
+impl fmt::Display for ParseGameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseGameError::InvalidFormat => {
+                f.write_str("expected `givens:entries` or `givens:entries:notes`")
+            }
+            ParseGameError::Givens(e) => write!(f, "invalid givens: {}", e),
+            ParseGameError::Entries(e) => write!(f, "invalid entries: {}", e),
+            ParseGameError::InvalidNotes(offset) => {
+                write!(f, "invalid notes at cell {}", offset + 1)
+            }
+            ParseGameError::EntryOnGiven(offset) => {
+                write!(f, "entry at cell {} overwrites a given", offset + 1)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseGameError {}
+
+/// writes the game in its compact single line form `givens:entries[:notes]`.
+///
+/// `givens` and `entries` are in the line format, where `entries` only has the numbers the
+/// player filled in. `notes` has three hex digits per cell (the bits of the notes mask) and is
+/// left out if there are no notes at all.
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut entries = self.current_numbers;
+        for (idx, given) in self.given_numbers.cells().enumerate() {
+            if given != 0 {
+                entries.0[idx] = 0;
+            }
+        }
+
+        write!(f, "{}:{}", self.given_numbers, entries)?;
+        if self.notes.iter().any(|&notes| notes != 0) {
+            f.write_str(":")?;
+            for notes in self.notes {
+                write!(f, "{:03x}", notes)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Game {
+    type Err = ParseGameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':');
+        let (givens, entries, notes) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(givens), Some(entries), notes) if parts.next().is_none() => {
+                (givens, entries, notes)
+            }
+            _ => return Err(ParseGameError::InvalidFormat),
+        };
+
+        let mut game = Game::create(givens.parse::<Numbers>().map_err(ParseGameError::Givens)?);
+
+        let entries: Numbers = entries.parse().map_err(ParseGameError::Entries)?;
+        for (idx, value) in entries.cells().enumerate() {
+            if value != 0 {
+                if game.index_is_given(idx) {
+                    return Err(ParseGameError::EntryOnGiven(idx));
+                }
+                game.current_numbers.0[idx] = value;
+            }
+        }
+
+        if let Some(notes) = notes {
+            if notes.len() != 81 * 3 || !notes.is_ascii() {
+                return Err(ParseGameError::InvalidFormat);
+            }
+
+            for idx in 0..81 {
+                game.notes[idx] = u16::from_str_radix(&notes[idx * 3..idx * 3 + 3], 16)
+                    .ok()
+                    .filter(|&notes| notes <= 0x01FF)
+                    .ok_or(ParseGameError::InvalidNotes(idx))?;
+            }
+        }
+
+        Ok(game)
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod test {
     use super::*;
 
@@ -290,6 +679,126 @@ mod test {
         }
     }
 
+    mod line_format {
+        use super::*;
+
+        #[test]
+        fn parses_dots_and_zeros_as_empty_cells() {
+            let line =
+                "1...6....98....6.5.....5..1......3.4.6.13.9...4.72.....93.761....648...75..9.246.";
+            let n: Numbers = line.parse().unwrap();
+            assert_eq!(Numbers::from(TEST_FIELD), n);
+            assert_eq!(n, line.replace('.', "0").parse().unwrap());
+        }
+
+        #[test]
+        fn display_round_trips() {
+            let n = Numbers::from(TEST_FIELD);
+            assert_eq!(n, n.to_string().parse().unwrap());
+        }
+
+        #[test]
+        fn rejects_bad_input() {
+            assert_eq!(
+                Err(ParseNumbersError::InvalidLength(3)),
+                "123".parse::<Numbers>()
+            );
+            let line = format!("x{}", ".".repeat(80));
+            assert_eq!(
+                Err(ParseNumbersError::InvalidCharacter(0, 'x')),
+                line.parse::<Numbers>()
+            );
+        }
+    }
+
+    mod compact_form {
+        use super::*;
+
+        #[test]
+        fn round_trips_entries_and_notes() {
+            let mut g = Game::create(TEST_FIELD);
+            g.set(1, 2, 2);
+            g.set_notes(1, 3, 0x0121);
+            let compact = g.to_string();
+
+            assert_eq!(81 * 5 + 2, compact.len());
+            assert_eq!(Ok(g), compact.parse());
+        }
+
+        #[test]
+        fn leaves_out_empty_notes() {
+            let g = Game::create(TEST_FIELD);
+            assert_eq!(
+                format!("{}:{}", Numbers::from(TEST_FIELD), ".".repeat(81)),
+                g.to_string()
+            );
+            assert_eq!(Ok(g), g.to_string().parse());
+        }
+
+        #[test]
+        fn rejects_entries_on_givens() {
+            let compact = format!("{}:2{}", Numbers::from(TEST_FIELD), ".".repeat(80));
+            assert_eq!(
+                Err(ParseGameError::EntryOnGiven(0)),
+                compact.parse::<Game>()
+            );
+            assert_eq!(Err(ParseGameError::InvalidFormat), "".parse::<Game>());
+        }
+    }
+
+    mod checked {
+        use super::*;
+
+        #[test]
+        fn newtypes_reject_out_of_range_values() {
+            assert_eq!(Err(RangeError::Row(0)), Row::new(0));
+            assert_eq!(Err(RangeError::Col(10)), Col::new(10));
+            assert_eq!(Err(RangeError::Digit(0)), Digit::new(0));
+            assert_eq!(Err(RangeError::CellIndex(81)), CellIndex::new(81));
+
+            let cell = CellIndex::new(80).unwrap();
+            assert_eq!((9, 9), (cell.row().get(), cell.col().get()));
+            assert_eq!(cell, CellIndex::from_coords(cell.row(), cell.col()));
+        }
+
+        #[test]
+        fn fallible_accessors_return_errors() {
+            let mut g = Game::create(TEST_FIELD);
+            assert_eq!(Ok(1), g.try_get(1, 1));
+            assert_eq!(Err(RangeError::Row(10)), g.try_get(10, 1));
+            assert_eq!(Err(RangeError::Digit(10)), g.try_set(1, 2, 10));
+            assert_eq!(Ok(false), g.try_set(1, 1, 2));
+            assert_eq!(Ok(true), g.try_set(1, 2, 2));
+            assert_eq!(Err(RangeError::Digit(0)), g.try_toggle_note(1, 2, 0));
+            assert_eq!(
+                Err(RangeError::Notes(0x0200)),
+                g.try_set_notes(1, 2, 0x0200)
+            );
+            assert_eq!(
+                Err(RangeError::CellIndex(81)),
+                Game::try_cell_index_to_coords(81)
+            );
+            assert_eq!(
+                Err(RangeError::Col(0)),
+                Game::try_coords_to_cell_index(1, 0)
+            );
+        }
+
+        #[test]
+        fn toggling_note_zero_does_nothing() {
+            let mut g = Game::create(TEST_FIELD);
+            g.toggle_note(1, 2, 0);
+            assert_eq!(Game::create(TEST_FIELD), g);
+        }
+
+        #[test]
+        fn out_of_range_numbers_are_invalid() {
+            let mut cells = TEST_FIELD;
+            cells[1] = 42;
+            assert!(!Game::create(cells).is_valid());
+        }
+    }
+
     #[test]
     fn test_cell_index_to_coord() {
         assert_eq!((9, 9), Game::cell_index_to_coords(80));
@@ -301,6 +810,27 @@ mod test {
         assert!(g.is_valid());
     }
 
+    #[test]
+    fn any_additional_one_is_invalid() {
+        let base = Game::create(VALIDATION_PATTERN);
+        assert!(base.is_valid());
+
+        for idx in 0..81 {
+            if base.index_is_given(idx) {
+                continue;
+            }
+
+            let (row, col) = Game::cell_index_to_coords(idx);
+            let mut g = base;
+            g.set(row, col, 1);
+            assert!(
+                !g.is_valid(),
+                "placing 1 at {:?} should be invalid",
+                (row, col)
+            );
+        }
+    }
+
     #[test]
     fn incorrect_number_in_row_invalid() {
         let mut g = Game::create(TEST_FIELD);
@@ -324,4 +854,53 @@ mod test {
 
         assert_eq!(false, g.is_valid());
     }
+
+    mod properties {
+        use proptest::prelude::*;
+
+        use super::*;
+
+        proptest! {
+            #[test]
+            fn cell_index_round_trips(index in 0usize..81) {
+                let (row, col) = Game::cell_index_to_coords(index);
+                prop_assert_eq!(index, Game::coords_to_cell_index(row, col));
+            }
+
+            #[test]
+            fn coords_round_trip(row in 1usize..=9, col in 1usize..=9) {
+                let index = Game::coords_to_cell_index(row, col);
+                prop_assert_eq!((row, col), Game::cell_index_to_coords(index));
+            }
+
+            #[test]
+            fn parse_and_display_round_trip(line in "[0-9.]{81}") {
+                let numbers: Numbers = line.parse().unwrap();
+                prop_assert_eq!(line.replace('0', "."), numbers.to_string());
+                prop_assert_eq!(numbers, numbers.to_string().parse().unwrap());
+            }
+
+            #[test]
+            fn parsing_never_panics(line in "\\PC{0,100}") {
+                let _ = line.parse::<Numbers>();
+            }
+
+            #[test]
+            fn toggle_note_is_an_involution(
+                row in 1usize..=9,
+                col in 1usize..=9,
+                notes in 0u16..=0x01FF,
+                note in 1u8..=9,
+            ) {
+                let mut game = Game::create(Numbers::empty());
+                game.set_notes(row, col, notes);
+                let before = game;
+
+                game.toggle_note(row, col, note);
+                prop_assert_ne!(before, game);
+                game.toggle_note(row, col, note);
+                prop_assert_eq!(before, game);
+            }
+        }
+    }
 }
@@ -0,0 +1,459 @@
+//! symmetry: the transformations that turn a valid sudoku into another valid one. relabeling
+//! the digits, permuting the rows within a band or the columns within a stack, permuting the
+//! bands or the stacks, and transposing.
+//!
+//! puzzles that one of these turns into each other are the same puzzle in disguise, they are
+//! solved with the same steps. [`canonical`] picks one representative for all of them, and a
+//! [`Transformation`] turns a puzzle or a game in progress into one of the others. rotating and
+//! mirroring are transformations too, made of the ones above.
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::sudoku::{Game, Numbers};
+
+const PERMUTATIONS: [[usize; 3]; 6] = [
+    [0, 1, 2],
+    [0, 2, 1],
+    [1, 0, 2],
+    [1, 2, 0],
+    [2, 0, 1],
+    [2, 1, 0],
+];
+
+/// every order of the 9 lines that keeps the bands together: the bands in any order, and the
+/// lines of each band in any order
+fn line_orders() -> Vec<[usize; 9]> {
+    let mut orders = Vec::with_capacity(6 * 6 * 6 * 6);
+    for bands in PERMUTATIONS {
+        for first in PERMUTATIONS {
+            for second in PERMUTATIONS {
+                for third in PERMUTATIONS {
+                    let mut order = [0; 9];
+                    for (band, lines) in [first, second, third].into_iter().enumerate() {
+                        for (i, line) in lines.into_iter().enumerate() {
+                            order[band * 3 + i] = bands[band] * 3 + line;
+                        }
+                    }
+                    orders.push(order);
+                }
+            }
+        }
+    }
+
+    orders
+}
+
+/// which cells of a line hold a digit, in the given order, first cell as the highest bit
+fn filled(grid: &[u8; 81], line: usize, order: &[usize; 9]) -> u16 {
+    order.iter().fold(0, |mask, &col| {
+        mask << 1 | u16::from(grid[line * 9 + col] != 0)
+    })
+}
+
+/// the canonical form of a puzzle: the smallest of all the puzzles it can be turned into, when
+/// they are compared cell by cell in the line format, with empty cells as 0.
+///
+/// the digits of each candidate are relabeled in the order they first appear, so the smallest
+/// one has its first digit as 1, its next new digit as 2 and so on. two puzzles are
+/// [equivalent](are_equivalent) exactly when their canonical forms are the same.
+///
+/// the first row of a candidate only depends on which of its cells are empty, so only the
+/// orders that give it the most leading empty cells are compared in full. with a digit twice in
+/// a line that's not always the smallest, but still the same for all equivalent puzzles.
+pub fn canonical(numbers: &Numbers) -> Numbers {
+    let cells: [u8; 81] = (*numbers).into();
+    let mut transposed = [0u8; 81];
+    for (idx, value) in transposed.iter_mut().enumerate() {
+        *value = cells[(idx % 9) * 9 + idx / 9];
+    }
+
+    let grids = [cells, transposed];
+    let orders = line_orders();
+    let mut first_row = u16::MAX;
+    for grid in &grids {
+        for line in 0..9 {
+            for cols in &orders {
+                first_row = first_row.min(filled(grid, line, cols));
+            }
+        }
+    }
+
+    let mut best = [u8::MAX; 81];
+    let mut candidate = [0u8; 81];
+    for grid in &grids {
+        let col_orders: Vec<Vec<&[usize; 9]>> = (0..9)
+            .map(|line| {
+                orders
+                    .iter()
+                    .filter(|cols| filled(grid, line, cols) == first_row)
+                    .collect()
+            })
+            .collect();
+
+        for rows in &orders {
+            for cols in &col_orders[rows[0]] {
+                let mut labels = [0u8; 10];
+                let mut next = 1;
+                let mut smaller = false;
+                let mut larger = false;
+                for idx in 0..81 {
+                    let value = grid[rows[idx / 9] * 9 + cols[idx % 9]];
+                    let value = if value == 0 {
+                        0
+                    } else {
+                        let label = &mut labels[usize::from(value)];
+                        if *label == 0 {
+                            *label = next;
+                            next += 1;
+                        }
+                        *label
+                    };
+
+                    candidate[idx] = value;
+                    if !smaller {
+                        if value > best[idx] {
+                            larger = true;
+                            break;
+                        }
+                        smaller = value < best[idx];
+                    }
+                }
+
+                if smaller && !larger {
+                    best = candidate;
+                }
+            }
+        }
+    }
+
+    Numbers::from(best)
+}
+
+/// whether one of the puzzles can be turned into the other
+pub fn are_equivalent(a: &Numbers, b: &Numbers) -> bool {
+    canonical(a) == canonical(b)
+}
+
+/// one transformation, or several in a row: where each cell comes from and what each digit
+/// becomes. rows, columns, bands and stacks are counted from 0.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Transformation {
+    /// the cell each cell of the result is taken from
+    cells: [usize; 81],
+    /// what each digit becomes, with 0 for an empty cell
+    digits: [u8; 10],
+}
+
+impl Default for Transformation {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Transformation {
+    /// leaves everything where it is
+    pub fn identity() -> Self {
+        Transformation {
+            cells: std::array::from_fn(|idx| idx),
+            digits: std::array::from_fn(|digit| digit as u8),
+        }
+    }
+
+    /// moves the cells, `from` gives the `(row, col)` each cell of the result is taken from
+    fn moving(from: impl Fn(usize, usize) -> (usize, usize)) -> Self {
+        Transformation {
+            cells: std::array::from_fn(|idx| {
+                let (row, col) = from(idx / 9, idx % 9);
+                row * 9 + col
+            }),
+            ..Self::identity()
+        }
+    }
+
+    /// a quarter turn clockwise
+    pub fn rotate() -> Self {
+        Self::moving(|row, col| (8 - col, row))
+    }
+
+    /// mirrors left to right
+    pub fn mirror() -> Self {
+        Self::moving(|row, col| (row, 8 - col))
+    }
+
+    /// mirrors along the diagonal from the top left
+    pub fn transpose() -> Self {
+        Self::moving(|row, col| (col, row))
+    }
+
+    /// turns each digit `d` into `digits[d - 1]`. panics if that's not every digit once.
+    pub fn permute_digits(digits: [u8; 9]) -> Self {
+        let mut sorted = digits;
+        sorted.sort_unstable();
+        assert_eq!([1, 2, 3, 4, 5, 6, 7, 8, 9], sorted, "not a permutation");
+
+        let mut transformation = Self::identity();
+        transformation.digits[1..].copy_from_slice(&digits);
+        transformation
+    }
+
+    /// swaps two rows of the same band. panics if they're in different bands.
+    pub fn swap_rows(a: usize, b: usize) -> Self {
+        assert!(
+            a < 9 && b < 9 && a / 3 == b / 3,
+            "rows {a} and {b} are not in one band"
+        );
+        Self::moving(|row, col| (swapped(row, a, b), col))
+    }
+
+    /// swaps two columns of the same stack. panics if they're in different stacks.
+    pub fn swap_cols(a: usize, b: usize) -> Self {
+        assert!(
+            a < 9 && b < 9 && a / 3 == b / 3,
+            "columns {a} and {b} are not in one stack"
+        );
+        Self::moving(|row, col| (row, swapped(col, a, b)))
+    }
+
+    /// swaps two bands of three rows
+    pub fn swap_bands(a: usize, b: usize) -> Self {
+        assert!(a < 3 && b < 3);
+        Self::moving(|row, col| (swapped(row / 3, a, b) * 3 + row % 3, col))
+    }
+
+    /// swaps two stacks of three columns
+    pub fn swap_stacks(a: usize, b: usize) -> Self {
+        assert!(a < 3 && b < 3);
+        Self::moving(|row, col| (row, swapped(col / 3, a, b) * 3 + col % 3))
+    }
+
+    /// a random one of all the transformations, each as likely
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let mut digits = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+        digits.shuffle(rng);
+        let rows = *line_orders().choose(rng).unwrap();
+        let cols = *line_orders().choose(rng).unwrap();
+
+        let moved = Self::moving(|row, col| (rows[row], cols[col]));
+        let moved = if rng.gen() {
+            Self::transpose().then(moved)
+        } else {
+            moved
+        };
+        moved.then(Self::permute_digits(digits))
+    }
+
+    /// this transformation followed by `next`
+    pub fn then(self, next: Self) -> Self {
+        Transformation {
+            cells: next.cells.map(|idx| self.cells[idx]),
+            digits: self.digits.map(|digit| next.digits[usize::from(digit)]),
+        }
+    }
+
+    fn digit(&self, value: u8) -> u8 {
+        self.digits
+            .get(usize::from(value))
+            .copied()
+            .unwrap_or(value)
+    }
+
+    fn notes(&self, notes: u16) -> u16 {
+        (1..=9u8)
+            .filter(|digit| notes & (1 << (digit - 1)) != 0)
+            .fold(0, |all, digit| all | 1 << (self.digit(digit) - 1))
+    }
+
+    /// the puzzle after this transformation
+    pub fn apply(&self, numbers: &Numbers) -> Numbers {
+        let cells: [u8; 81] = (*numbers).into();
+        Numbers::from(self.cells.map(|idx| self.digit(cells[idx])))
+    }
+
+    /// transforms the givens, the entries and the notes of a game alike
+    pub fn apply_game(&self, game: &Game) -> Game {
+        let current = self.apply(&game.current_numbers());
+        let mut transformed = Game::create(self.apply(&game.given_numbers()));
+        for (idx, &from) in self.cells.iter().enumerate() {
+            let (row, col) = Game::cell_index_to_coords(idx);
+            let (from_row, from_col) = Game::cell_index_to_coords(from);
+            transformed.set(row, col, current.get_by_offset(idx));
+            transformed.set_notes(row, col, self.notes(game.get_notes(from_row, from_col)));
+        }
+
+        transformed
+    }
+}
+
+/// swaps `a` and `b`, and leaves other values alone
+fn swapped(value: usize, a: usize, b: usize) -> usize {
+    if value == a {
+        b
+    } else if value == b {
+        a
+    } else {
+        value
+    }
+}
+
+/// the puzzle after a [random](Transformation::random) transformation, which looks different
+/// but is just as hard
+pub fn random_isomorph<R: Rng + ?Sized>(numbers: &Numbers, rng: &mut R) -> Numbers {
+    Transformation::random(rng).apply(numbers)
+}
+
+/// a symmetry of the pattern of givens, which puzzles often keep for looks
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Symmetry {
+    None,
+    /// a half turn leaves the pattern as it is
+    Rotational,
+    /// mirroring left to right leaves the pattern as it is
+    Mirror,
+    /// mirroring along the diagonal from the top left leaves the pattern as it is
+    Diagonal,
+}
+
+impl Symmetry {
+    /// the cell this symmetry maps the cell to, which may be the cell itself
+    pub fn partner(self, idx: usize) -> usize {
+        let (row, col) = (idx / 9, idx % 9);
+        match self {
+            Symmetry::None => idx,
+            Symmetry::Rotational => 80 - idx,
+            Symmetry::Mirror => row * 9 + 8 - col,
+            Symmetry::Diagonal => col * 9 + row,
+        }
+    }
+
+    /// whether the pattern of givens looks the same after the symmetry
+    pub fn is_kept_by(self, numbers: &Numbers) -> bool {
+        (0..81).all(|idx| {
+            (numbers.get_by_offset(idx) != 0) == (numbers.get_by_offset(self.partner(idx)) != 0)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    const PUZZLE: &str =
+        "1...6....98....6.5.....5..1......3.4.6.13.9...4.72.....93.761....648...75..9.246.";
+
+    /// the puzzle transposed, with the first two bands and the digits 1 and 9 swapped
+    fn disguised(numbers: &Numbers) -> Numbers {
+        let cells: [u8; 81] = (*numbers).into();
+        let mut changed = [0u8; 81];
+        for (idx, value) in changed.iter_mut().enumerate() {
+            let (row, col) = (idx / 9, idx % 9);
+            let row = match row / 3 {
+                0 => row + 3,
+                1 => row - 3,
+                _ => row,
+            };
+            *value = match cells[col * 9 + row] {
+                1 => 9,
+                9 => 1,
+                x => x,
+            };
+        }
+
+        Numbers::from(changed)
+    }
+
+    #[test]
+    fn disguised_puzzles_are_equivalent() {
+        let puzzle: Numbers = PUZZLE.parse().unwrap();
+        let other = disguised(&puzzle);
+        assert_ne!(puzzle, other);
+        assert!(are_equivalent(&puzzle, &other));
+        assert_eq!(canonical(&puzzle), canonical(&other));
+    }
+
+    #[test]
+    fn canonical_form_is_its_own_canonical_form() {
+        let puzzle: Numbers = PUZZLE.parse().unwrap();
+        let canonical_form = canonical(&puzzle);
+        assert_eq!(canonical_form, canonical(&canonical_form));
+        assert_eq!(
+            puzzle.cells().filter(|&value| value != 0).count(),
+            canonical_form.cells().filter(|&value| value != 0).count()
+        );
+    }
+
+    #[test]
+    fn different_puzzles_are_not_equivalent() {
+        let puzzle: Numbers = PUZZLE.parse().unwrap();
+        let mut cells: [u8; 81] = puzzle.into();
+        // an empty cell becomes a given, which no transformation can undo
+        cells[1] = 2;
+        assert!(!are_equivalent(&puzzle, &Numbers::from(cells)));
+    }
+
+    #[test]
+    fn four_rotations_are_nothing() {
+        let rotate = Transformation::rotate();
+        let full = rotate.then(rotate).then(rotate).then(rotate);
+        assert_eq!(Transformation::identity(), full);
+        assert_eq!(
+            Transformation::mirror().then(Transformation::mirror()),
+            Transformation::identity()
+        );
+    }
+
+    #[test]
+    fn transformations_keep_puzzles_equivalent() {
+        let puzzle: Numbers = PUZZLE.parse().unwrap();
+        let transformations = [
+            Transformation::rotate(),
+            Transformation::mirror(),
+            Transformation::transpose(),
+            Transformation::permute_digits([9, 8, 7, 6, 5, 4, 3, 2, 1]),
+            Transformation::swap_rows(3, 5),
+            Transformation::swap_cols(0, 2),
+            Transformation::swap_bands(0, 2),
+            Transformation::swap_stacks(1, 2),
+        ];
+        for transformation in transformations {
+            let transformed = transformation.apply(&puzzle);
+            assert!(Game::create(transformed).is_valid());
+            assert!(are_equivalent(&puzzle, &transformed));
+        }
+
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..5 {
+            assert!(are_equivalent(&puzzle, &random_isomorph(&puzzle, &mut rng)));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn rows_of_different_bands_cannot_be_swapped() {
+        Transformation::swap_rows(2, 3);
+    }
+
+    #[test]
+    fn games_keep_givens_entries_and_notes_together() {
+        let mut game = Game::create(PUZZLE.parse::<Numbers>().unwrap());
+        game.set(1, 2, 2);
+        game.set_notes(1, 3, 0b101);
+
+        let transformation = Transformation::rotate()
+            .then(Transformation::permute_digits([2, 1, 3, 4, 5, 6, 7, 8, 9]));
+        let transformed = transformation.apply_game(&game);
+        // r1c1 moves to r1c9, r1c2 to r2c9 and r1c3 to r3c9
+        assert!(transformed.is_given(1, 9));
+        assert_eq!(2, transformed.get(1, 9));
+        assert!(!transformed.is_given(2, 9));
+        assert_eq!(1, transformed.get(2, 9));
+        assert_eq!(0b110, transformed.get_notes(3, 9));
+        assert_eq!(
+            transformation.apply(&game.given_numbers()),
+            transformed.given_numbers()
+        );
+    }
+}
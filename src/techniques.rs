@@ -0,0 +1,107 @@
+//! the solving techniques beyond singles, grouped by the kind of pattern they look for.
+//!
+//! every technique is a [`SolverStep`](crate::solver::SolverStep) that applies the first
+//! pattern it finds, so the solve log shows one pattern per step.
+
+pub mod als;
+pub mod chains;
+pub mod coloring;
+pub mod fish;
+pub mod intersections;
+pub mod single_digit;
+pub mod subsets;
+pub mod uniqueness;
+pub mod wings;
+
+/// calls `f` with every subset of `size` items, until it returns something
+pub(crate) fn subsets<T>(
+    items: &[usize],
+    size: usize,
+    chosen: &mut Vec<usize>,
+    f: &mut impl FnMut(&[usize]) -> Option<T>,
+) -> Option<T> {
+    if chosen.len() == size {
+        return f(chosen);
+    }
+
+    for (i, &item) in items.iter().enumerate() {
+        chosen.push(item);
+        let found = subsets(&items[i + 1..], size, chosen, f);
+        chosen.pop();
+        if found.is_some() {
+            return found;
+        }
+    }
+
+    None
+}
+
+/// the notes mask of a digit, with bit 0 for digit 1
+pub(crate) fn bit(digit: u8) -> u16 {
+    1 << (digit - 1)
+}
+
+/// the digits in a notes mask, smallest first
+pub(crate) fn digits(notes: u16) -> impl Iterator<Item = u8> {
+    (1..=9u8).filter(move |&digit| notes & bit(digit) != 0)
+}
+
+/// the number of digits in a notes mask
+pub(crate) fn count(notes: u16) -> usize {
+    notes.count_ones() as usize
+}
+
+/// builders for the small grids the technique tests start from
+#[cfg(test)]
+pub(crate) mod test_util {
+    use crate::bitboard::{Bitboard, Candidates, HOUSES};
+    use crate::solver::SolverStep;
+    use crate::sudoku::Numbers;
+
+    /// a grid where every cell has all candidates, except for the given `(row, col)` cells
+    pub(crate) fn with_notes(cells: &[(usize, usize, &[u8])]) -> Candidates {
+        let mut candidates = Candidates::from_numbers(&Numbers::empty());
+        for &(row, col, digits) in cells {
+            for digit in (1..=9).filter(|d| !digits.contains(d)) {
+                candidates.eliminate(digit, Bitboard::cell(row * 9 + col));
+            }
+        }
+
+        candidates
+    }
+
+    /// a grid where `digit` is a candidate everywhere, except that it is limited to the given
+    /// `(row, col)` cells within each of the given houses
+    pub(crate) fn restricted(digit: u8, houses: &[(usize, &[(usize, usize)])]) -> Candidates {
+        let mut candidates = Candidates::from_numbers(&Numbers::empty());
+        for &(house, allowed) in houses {
+            candidates.eliminate(digit, HOUSES[house] & !cells(allowed));
+        }
+
+        candidates
+    }
+
+    pub(crate) fn cells(cells: &[(usize, usize)]) -> Bitboard {
+        cells.iter().fold(Bitboard::EMPTY, |all, &(row, col)| {
+            all | Bitboard::cell(row * 9 + col)
+        })
+    }
+
+    /// the cells that lose `digit` when the step is applied, which it must be
+    pub(crate) fn eliminated(
+        step: &dyn SolverStep,
+        candidates: &Candidates,
+        digit: u8,
+    ) -> Bitboard {
+        let mut after = *candidates;
+        assert!(step.apply_candidates(&mut after));
+        candidates.digit(digit) & !after.digit(digit)
+    }
+
+    pub(crate) fn described(step: &dyn SolverStep, candidates: &Candidates) -> Vec<String> {
+        step.pattern(candidates)
+            .iter()
+            .map(|part| part.to_string())
+            .collect()
+    }
+}
@@ -0,0 +1,453 @@
+//! almost locked sets: `n` cells in one house that hold `n + 1` candidates between them. taking
+//! away any one of the candidates leaves a locked set, which has to hold all the others.
+//!
+//! two sets are joined by a restricted common candidate when every place of that candidate in
+//! one set sees every place of it in the other: it can be in at most one of the two sets.
+
+use crate::bitboard::{Bitboard, Candidates, HOUSES, PEERS};
+use crate::solver::{PatternPart, SolverStep};
+use crate::techniques::{bit, digits};
+
+/// an almost locked set, with the places of every digit and the cells that see all of them
+#[derive(Debug)]
+struct Als {
+    cells: Bitboard,
+    notes: u16,
+    places: [Bitboard; 9],
+    seen: [Bitboard; 9],
+}
+
+impl Als {
+    fn new(candidates: &Candidates, cells: Bitboard, notes: u16) -> Self {
+        let mut places = [Bitboard::EMPTY; 9];
+        let mut seen = [Bitboard::EMPTY; 9];
+        for digit in digits(notes) {
+            let i = usize::from(digit - 1);
+            places[i] = cells & candidates.digit(digit);
+            seen[i] = places[i]
+                .iter()
+                .fold(Bitboard::ALL, |seen, cell| seen & PEERS[cell]);
+        }
+
+        Als {
+            cells,
+            notes,
+            places,
+            seen,
+        }
+    }
+
+    fn places(&self, digit: u8) -> Bitboard {
+        self.places[usize::from(digit - 1)]
+    }
+
+    /// the cells outside the set that see every place of the digit in it
+    fn seen(&self, digit: u8) -> Bitboard {
+        self.seen[usize::from(digit - 1)]
+    }
+
+    fn part(&self, role: &str) -> PatternPart {
+        PatternPart::new(role, self.cells, self.notes)
+    }
+}
+
+/// every almost locked set, smallest first. a set that lies in several houses, like a single
+/// cell or two cells of a row within one box, is only listed for the first of them.
+fn almost_locked_sets(candidates: &Candidates) -> Vec<Als> {
+    let unsolved = candidates.unsolved();
+    let mut sets = vec![];
+    for (house, &house_cells) in HOUSES.iter().enumerate() {
+        let cells: Vec<usize> = (house_cells & unsolved).iter().collect();
+        let mut notes = vec![0u16; 1 << cells.len()];
+        for mask in 1..notes.len() {
+            let lowest = mask.trailing_zeros() as usize;
+            notes[mask] = notes[mask & (mask - 1)] | candidates.cell(cells[lowest]);
+            if notes[mask].count_ones() != mask.count_ones() + 1 {
+                continue;
+            }
+
+            let set = cells
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| mask & (1 << i) != 0)
+                .fold(Bitboard::EMPTY, |set, (_, &cell)| {
+                    set | Bitboard::cell(cell)
+                });
+            if HOUSES[..house]
+                .iter()
+                .any(|&other| (set & !other).is_empty())
+            {
+                continue;
+            }
+
+            sets.push(Als::new(candidates, set, notes[mask]));
+        }
+    }
+
+    sets.sort_by_key(|set| set.cells.count());
+    sets
+}
+
+/// the restricted common candidates of two sets that don't overlap
+fn restricted_commons(a: &Als, b: &Als) -> u16 {
+    if !(a.cells & b.cells).is_empty() {
+        return 0;
+    }
+
+    digits(a.notes & b.notes)
+        .filter(|&digit| (a.places(digit) & !b.seen(digit)).is_empty())
+        .fold(0, |rccs, digit| rccs | bit(digit))
+}
+
+/// the pattern of the sets and the cells it removes digits from
+struct Found {
+    parts: Vec<PatternPart>,
+    eliminations: Vec<(u8, Bitboard)>,
+}
+
+impl Found {
+    fn new(parts: Vec<PatternPart>) -> Self {
+        Found {
+            parts,
+            eliminations: vec![],
+        }
+    }
+
+    fn eliminate(&mut self, candidates: &Candidates, digit: u8, cells: Bitboard) {
+        let cells = cells & candidates.digit(digit);
+        if !cells.is_empty() {
+            self.eliminations.push((digit, cells));
+        }
+    }
+
+    fn found(self) -> Option<Self> {
+        (!self.eliminations.is_empty()).then_some(self)
+    }
+
+    fn apply(self, candidates: &mut Candidates) -> bool {
+        self.eliminations
+            .into_iter()
+            .fold(false, |changed, (digit, cells)| {
+                candidates.eliminate(digit, cells) | changed
+            })
+    }
+}
+
+fn restricted_common_part(a: &Als, b: &Als, digit: u8) -> PatternPart {
+    PatternPart::new(
+        "restricted common",
+        a.places(digit) | b.places(digit),
+        bit(digit),
+    )
+}
+
+/// two sets joined by a restricted common candidate x: x is in at most one of them, so the
+/// other is locked, and a digit z of both is in one of them. it can't be where all of its
+/// places in both sets are seen. with a second restricted common candidate both sets are
+/// locked, and every digit can be removed from the cells that see all of its places.
+#[derive(Debug)]
+pub struct AlsXzStep;
+
+fn als_xz(candidates: &Candidates) -> Option<Found> {
+    let sets = almost_locked_sets(candidates);
+    for (i, a) in sets.iter().enumerate() {
+        for b in &sets[i + 1..] {
+            let rccs = restricted_commons(a, b);
+            if rccs == 0 {
+                continue;
+            }
+
+            let mut parts = vec![a.part("als"), b.part("als")];
+            parts.extend(digits(rccs).map(|digit| restricted_common_part(a, b, digit)));
+            let mut found = Found::new(parts);
+            if rccs.count_ones() == 1 {
+                for digit in digits(a.notes & b.notes & !rccs) {
+                    found.eliminate(candidates, digit, a.seen(digit) & b.seen(digit));
+                }
+            } else {
+                for digit in digits(rccs) {
+                    found.eliminate(candidates, digit, a.seen(digit) & b.seen(digit));
+                }
+                for set in [a, b] {
+                    for digit in digits(set.notes & !rccs) {
+                        found.eliminate(candidates, digit, set.seen(digit));
+                    }
+                }
+            }
+
+            if let Some(found) = found.found() {
+                return Some(found);
+            }
+        }
+    }
+
+    None
+}
+
+impl SolverStep for AlsXzStep {
+    fn name(&self) -> &'static str {
+        "ALS-XZ"
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        als_xz(candidates).is_some_and(|found| found.apply(candidates))
+    }
+
+    fn pattern(&self, candidates: &Candidates) -> Vec<PatternPart> {
+        als_xz(candidates).map_or(vec![], |found| found.parts)
+    }
+}
+
+/// a pivot set joined to two other sets by different restricted common candidates x and y: the
+/// pivot can't lose both, so one of the other sets is locked, and a digit z of both of them is
+/// in one of them
+#[derive(Debug)]
+pub struct AlsXyWingStep;
+
+fn als_xy_wing(candidates: &Candidates) -> Option<Found> {
+    let sets = almost_locked_sets(candidates);
+    let mut linked = vec![vec![]; sets.len()];
+    for i in 0..sets.len() {
+        for j in i + 1..sets.len() {
+            let rccs = restricted_commons(&sets[i], &sets[j]);
+            if rccs != 0 {
+                linked[i].push((j, rccs));
+                linked[j].push((i, rccs));
+            }
+        }
+    }
+
+    for (pivot, links) in sets.iter().zip(&linked) {
+        for (k, &(i, a_rccs)) in links.iter().enumerate() {
+            for &(j, b_rccs) in &links[k + 1..] {
+                let (a, b) = (&sets[i], &sets[j]);
+                for x in digits(a_rccs) {
+                    for y in digits(b_rccs & !bit(x)) {
+                        let mut found = Found::new(vec![
+                            pivot.part("pivot"),
+                            a.part("als"),
+                            b.part("als"),
+                            restricted_common_part(pivot, a, x),
+                            restricted_common_part(pivot, b, y),
+                        ]);
+                        for z in digits(a.notes & b.notes & !bit(x) & !bit(y)) {
+                            found.eliminate(candidates, z, a.seen(z) & b.seen(z));
+                        }
+
+                        if let Some(found) = found.found() {
+                            return Some(found);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    None
+}
+
+impl SolverStep for AlsXyWingStep {
+    fn name(&self) -> &'static str {
+        "ALS-XY-Wing"
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        als_xy_wing(candidates).is_some_and(|found| found.apply(candidates))
+    }
+
+    fn pattern(&self, candidates: &Candidates) -> Vec<PatternPart> {
+        als_xy_wing(candidates).map_or(vec![], |found| found.parts)
+    }
+}
+
+/// a stem cell with a petal set for each of its candidates, where every place of that
+/// candidate in the petal sees the stem: whichever the stem is, its petal is locked. a digit z
+/// of every petal can't be where all of its places in the petals are seen, along with the stem
+/// if z is one of its candidates.
+#[derive(Debug)]
+pub struct DeathBlossomStep;
+
+/// picks a petal for each of the remaining stem digits, keeping only the cells that still see
+/// all places of z
+fn blossom<'a>(
+    sets: &'a [Als],
+    petals: &[Vec<usize>],
+    stem: usize,
+    stem_digits: &[u8],
+    z: u8,
+    seen: Bitboard,
+    chosen: &mut Vec<(u8, &'a Als)>,
+) -> Option<Bitboard> {
+    let Some((&digit, rest)) = stem_digits.split_first() else {
+        return Some(seen);
+    };
+
+    if digit == z {
+        return blossom(sets, petals, stem, rest, z, seen & PEERS[stem], chosen);
+    }
+
+    for &i in &petals[usize::from(digit - 1)] {
+        let petal = &sets[i];
+        if petal.notes & bit(z) == 0 {
+            continue;
+        }
+
+        let seen = seen & petal.seen(z);
+        if seen.is_empty() {
+            continue;
+        }
+
+        chosen.push((digit, petal));
+        if let Some(seen) = blossom(sets, petals, stem, rest, z, seen, chosen) {
+            return Some(seen);
+        }
+        chosen.pop();
+    }
+
+    None
+}
+
+fn death_blossom(candidates: &Candidates) -> Option<Found> {
+    let sets = almost_locked_sets(candidates);
+    for stem in candidates.unsolved() {
+        let notes = candidates.cell(stem);
+        let stem_digits: Vec<u8> = digits(notes).collect();
+        let petals: Vec<Vec<usize>> = (1..=9u8)
+            .map(|digit| {
+                (0..sets.len())
+                    .filter(|&i| {
+                        let set = &sets[i];
+                        notes & bit(digit) != 0
+                            && !set.cells.contains(stem)
+                            && set.notes & bit(digit) != 0
+                            && (set.places(digit) & !PEERS[stem]).is_empty()
+                    })
+                    .collect()
+            })
+            .collect();
+
+        for z in 1..=9u8 {
+            let mut chosen = vec![];
+            let seen = candidates.digit(z) & !Bitboard::cell(stem);
+            let Some(eliminations) =
+                blossom(&sets, &petals, stem, &stem_digits, z, seen, &mut chosen)
+            else {
+                continue;
+            };
+
+            let mut parts = vec![PatternPart::new("stem", Bitboard::cell(stem), notes)];
+            for &(digit, petal) in &chosen {
+                parts.push(petal.part("petal"));
+                parts.push(PatternPart::new(
+                    "restricted common",
+                    Bitboard::cell(stem) | petal.places(digit),
+                    bit(digit),
+                ));
+            }
+            let mut found = Found::new(parts);
+            found.eliminate(candidates, z, eliminations);
+            return found.found();
+        }
+    }
+
+    None
+}
+
+impl SolverStep for DeathBlossomStep {
+    fn name(&self) -> &'static str {
+        "Death Blossom"
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        death_blossom(candidates).is_some_and(|found| found.apply(candidates))
+    }
+
+    fn pattern(&self, candidates: &Candidates) -> Vec<PatternPart> {
+        death_blossom(candidates).map_or(vec![], |found| found.parts)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::techniques::test_util::{cells, described, eliminated, with_notes};
+
+    #[test]
+    fn finds_almost_locked_sets() {
+        let candidates = with_notes(&[(0, 0, &[1, 2]), (0, 1, &[1, 3]), (1, 0, &[2, 3])]);
+        let sets: Vec<Bitboard> = almost_locked_sets(&candidates)
+            .iter()
+            .map(|set| set.cells)
+            .collect();
+        assert!(sets.contains(&cells(&[(0, 0)])));
+        assert!(!sets.contains(&cells(&[(0, 0), (0, 1), (1, 0)])));
+        assert_eq!(
+            1,
+            sets.iter()
+                .filter(|&&set| set == cells(&[(0, 0), (0, 1)]))
+                .count()
+        );
+    }
+
+    #[test]
+    fn lists_each_almost_locked_set_once() {
+        let candidates = with_notes(&[(0, 0, &[1, 2]), (0, 1, &[1, 3]), (1, 0, &[2, 3])]);
+        let sets: Vec<Bitboard> = almost_locked_sets(&candidates)
+            .iter()
+            .map(|set| set.cells)
+            .collect();
+        let distinct: HashSet<Bitboard> = sets.iter().copied().collect();
+        assert_eq!(distinct.len(), sets.len());
+        // the three bivalue cells, which lie in a row, a column and a box each
+        assert_eq!(3, sets.iter().filter(|set| set.count() == 1).count());
+    }
+
+    #[test]
+    fn finds_als_xz() {
+        let candidates = with_notes(&[(0, 0, &[1, 2]), (4, 0, &[2, 3]), (4, 4, &[1, 3])]);
+        assert_eq!(cells(&[(0, 4)]), eliminated(&AlsXzStep, &candidates, 1));
+        assert_eq!(
+            vec![
+                "als r1c1 {12}",
+                "als r5c15 {123}",
+                "restricted common r15c1 {2}"
+            ],
+            described(&AlsXzStep, &candidates)
+        );
+    }
+
+    #[test]
+    fn finds_doubly_linked_als_xz() {
+        let candidates = with_notes(&[(0, 0, &[1, 2]), (0, 4, &[1, 2, 3]), (0, 5, &[1, 3])]);
+        let mut after = candidates;
+        assert!(AlsXzStep.apply_candidates(&mut after));
+        // 1 and 2 are locked in the row, and 3 in the two cells of the second set
+        let row = HOUSES[0] & !cells(&[(0, 0), (0, 4), (0, 5)]);
+        for digit in 1..=3 {
+            assert!((after.digit(digit) & row).is_empty());
+        }
+        assert!((after.digit(3) & HOUSES[19]).count() < (candidates.digit(3) & HOUSES[19]).count());
+    }
+
+    #[test]
+    fn finds_als_xy_wing() {
+        let candidates = with_notes(&[(4, 4, &[1, 2]), (0, 4, &[1, 3]), (4, 0, &[2, 3])]);
+        assert_eq!(cells(&[(0, 0)]), eliminated(&AlsXyWingStep, &candidates, 3));
+    }
+
+    #[test]
+    fn finds_death_blossom() {
+        let candidates = with_notes(&[
+            (4, 4, &[1, 2, 4]),
+            (4, 0, &[1, 3]),
+            (4, 1, &[2, 3]),
+            (3, 3, &[3, 4]),
+        ]);
+        assert_eq!(
+            cells(&[(3, 0), (3, 1), (3, 2), (4, 3), (4, 5)]),
+            eliminated(&DeathBlossomStep, &candidates, 3)
+        );
+    }
+}
@@ -0,0 +1,737 @@
+//! chains over the inference graph of all candidates.
+//!
+//! a candidate is a node of the graph. two candidates are strongly linked when at least one of
+//! them is true (the only two candidates of a cell, or the only two places of a digit in a
+//! house) and weakly linked when at most one of them is (two candidates of a cell, or the same
+//! digit in cells that see each other). a chain that alternates between the two, starting and
+//! ending with a strong link, proves that one of its ends is true.
+
+use std::collections::VecDeque;
+
+use crate::bitboard::{houses_of, Bitboard, Candidates, HOUSES, PEERS};
+use crate::solver::{Chain, ChainNode, Link, PatternPart, SolverStep};
+use crate::techniques::digits;
+
+/// the most links a chain may have when a step isn't told otherwise
+pub const MAX_LENGTH: usize = 12;
+
+/// the node of a candidate
+fn node(cell: usize, digit: u8) -> usize {
+    cell * 9 + digit as usize - 1
+}
+
+fn cell_of(node: usize) -> usize {
+    node / 9
+}
+
+fn digit_of(node: usize) -> u8 {
+    (node % 9) as u8 + 1
+}
+
+/// the links between the candidates of a grid
+struct Graph {
+    nodes: Vec<usize>,
+    strong: Vec<Vec<usize>>,
+    weak: Vec<Vec<usize>>,
+}
+
+impl Graph {
+    /// the graph of all links, or for xy-chains only the strong links within cells and the weak
+    /// links between cells
+    fn new(candidates: &Candidates, xy_only: bool) -> Self {
+        let mut graph = Graph {
+            nodes: vec![],
+            strong: vec![vec![]; 81 * 9],
+            weak: vec![vec![]; 81 * 9],
+        };
+
+        for cell in candidates.unsolved() {
+            let notes = candidates.cell(cell);
+            for digit in digits(notes) {
+                let from = node(cell, digit);
+                graph.nodes.push(from);
+
+                let others = notes & !(1 << (digit - 1));
+                if others.count_ones() == 1 {
+                    graph.strong[from].push(node(cell, digits(others).next().unwrap()));
+                }
+                if !xy_only {
+                    for house in houses_of(cell) {
+                        let places = candidates.digit(digit) & HOUSES[house];
+                        if places.count() == 2 {
+                            let to = node((places & !Bitboard::cell(cell)).first().unwrap(), digit);
+                            if !graph.strong[from].contains(&to) {
+                                graph.strong[from].push(to);
+                            }
+                        }
+                    }
+                    graph.weak[from].extend(digits(others).map(|other| node(cell, other)));
+                }
+                graph.weak[from].extend(
+                    (candidates.digit(digit) & PEERS[cell])
+                        .iter()
+                        .map(|peer| node(peer, digit)),
+                );
+            }
+        }
+
+        graph
+    }
+}
+
+/// the candidates weakly linked to both `a` and `b`, which can't be true when one of them is
+fn seen_by_both(candidates: &Candidates, a: usize, b: usize) -> Vec<usize> {
+    let (cell_a, digit_a) = (cell_of(a), digit_of(a));
+    let (cell_b, digit_b) = (cell_of(b), digit_of(b));
+    if a == b {
+        vec![]
+    } else if digit_a == digit_b {
+        (candidates.digit(digit_a) & PEERS[cell_a] & PEERS[cell_b])
+            .iter()
+            .map(|cell| node(cell, digit_a))
+            .collect()
+    } else if cell_a == cell_b {
+        digits(candidates.cell(cell_a))
+            .filter(|&digit| digit != digit_a && digit != digit_b)
+            .map(|digit| node(cell_a, digit))
+            .collect()
+    } else if PEERS[cell_a].contains(cell_b) {
+        [node(cell_a, digit_b), node(cell_b, digit_a)]
+            .into_iter()
+            .filter(|&n| candidates.digit(digit_of(n)).contains(cell_of(n)))
+            .collect()
+    } else {
+        vec![]
+    }
+}
+
+fn to_chain(nodes: &[usize], links: Vec<Link>) -> Chain {
+    Chain {
+        nodes: nodes
+            .iter()
+            .map(|&n| ChainNode::cell(cell_of(n), digit_of(n)))
+            .collect(),
+        links,
+    }
+}
+
+fn alternating(nodes: &[usize], first: Link) -> Vec<Link> {
+    (0..nodes.len().saturating_sub(1))
+        .map(|i| match (first, i % 2) {
+            (Link::Strong, 0) | (Link::Weak, 1) => Link::Strong,
+            _ => Link::Weak,
+        })
+        .collect()
+}
+
+/// what a chain proves, with the chain behind it
+struct Found {
+    placements: Vec<usize>,
+    eliminations: Vec<usize>,
+    chain: Option<Chain>,
+    branches: Vec<Chain>,
+    pattern: Vec<PatternPart>,
+}
+
+impl Found {
+    fn apply(self, candidates: &mut Candidates) -> bool {
+        let mut changed = false;
+        for n in self.placements {
+            if candidates.value(cell_of(n)) == 0 {
+                candidates.place(cell_of(n), digit_of(n));
+                changed = true;
+            }
+        }
+        for n in self.eliminations {
+            changed |= candidates.eliminate(digit_of(n), Bitboard::cell(cell_of(n)));
+        }
+
+        changed
+    }
+}
+
+/// walks the alternating paths from `start` breadth first, so shorter ones come first, and
+/// calls `visit` with every path that ends with a strong link, until it returns something.
+///
+/// the first link is strong if `first` is, the path in `visit` may end on `start` again.
+fn search<T>(
+    graph: &Graph,
+    start: usize,
+    first: Link,
+    max_length: usize,
+    mut visit: impl FnMut(&[usize]) -> Option<T>,
+) -> Option<T> {
+    // the state is a node and whether the next link has to be strong
+    let first_layer = usize::from(first == Link::Strong);
+    let mut parent = [vec![None; 81 * 9], vec![None; 81 * 9]];
+    let mut seen = [vec![false; 81 * 9], vec![false; 81 * 9]];
+    seen[first_layer][start] = true;
+    let mut queue = VecDeque::from([(start, first_layer, 0)]);
+
+    let path_to = |parent: &[Vec<Option<usize>>; 2], end: usize, layer: usize| {
+        let mut path = vec![end];
+        let (mut at, mut layer) = (end, layer);
+        while let Some(previous) = parent[layer][at] {
+            path.push(previous);
+            (at, layer) = (previous, 1 - layer);
+        }
+        path.reverse();
+        path
+    };
+
+    let is_simple = |path: &[usize]| {
+        let mut used = path.to_vec();
+        used.sort_unstable();
+        used.dedup();
+        used.len() == path.len()
+    };
+
+    while let Some((at, layer, length)) = queue.pop_front() {
+        if length == max_length {
+            continue;
+        }
+
+        let strong = layer == 1;
+        let next = if strong {
+            &graph.strong[at]
+        } else {
+            &graph.weak[at]
+        };
+        for &end in next {
+            if end == start {
+                if strong {
+                    let mut path = path_to(&parent, at, layer);
+                    if is_simple(&path) {
+                        path.push(start);
+                        if let Some(found) = visit(&path) {
+                            return Some(found);
+                        }
+                    }
+                }
+                continue;
+            }
+
+            let end_layer = 1 - layer;
+            if seen[end_layer][end] {
+                continue;
+            }
+            seen[end_layer][end] = true;
+            parent[end_layer][end] = Some(at);
+            queue.push_back((end, end_layer, length + 1));
+
+            if strong {
+                let path = path_to(&parent, end, end_layer);
+                if is_simple(&path) {
+                    if let Some(found) = visit(&path) {
+                        return Some(found);
+                    }
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// the first open chain of at least three links whose ends eliminate something
+fn open_chain(candidates: &Candidates, graph: &Graph, max_length: usize) -> Option<Found> {
+    graph.nodes.iter().find_map(|&start| {
+        search(graph, start, Link::Strong, max_length, |path| {
+            let end = *path.last().unwrap();
+            if path.len() < 4 || end == start {
+                return None;
+            }
+
+            let eliminations: Vec<usize> = seen_by_both(candidates, start, end)
+                .into_iter()
+                .filter(|n| !path.contains(n))
+                .collect();
+            (!eliminations.is_empty()).then(|| Found {
+                placements: vec![],
+                eliminations,
+                chain: Some(to_chain(path, alternating(path, Link::Strong))),
+                branches: vec![],
+                pattern: vec![],
+            })
+        })
+    })
+}
+
+/// a chain of cells with two candidates each, where neighbouring cells share a digit: one of
+/// the digits at the ends is true
+#[derive(Debug, Clone, Copy)]
+pub struct XyChainStep {
+    /// the most links a chain may have
+    pub max_length: usize,
+}
+
+impl Default for XyChainStep {
+    fn default() -> Self {
+        XyChainStep {
+            max_length: MAX_LENGTH,
+        }
+    }
+}
+
+impl XyChainStep {
+    fn find(&self, candidates: &Candidates) -> Option<Found> {
+        open_chain(candidates, &Graph::new(candidates, true), self.max_length)
+    }
+}
+
+impl SolverStep for XyChainStep {
+    fn name(&self) -> &'static str {
+        "XY-Chain"
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        self.find(candidates)
+            .is_some_and(|found| found.apply(candidates))
+    }
+
+    fn chain(&self, candidates: &Candidates) -> Option<Chain> {
+        self.find(candidates).and_then(|found| found.chain)
+    }
+}
+
+/// an alternating inference chain over any links: a candidate that sees both ends is false
+#[derive(Debug, Clone, Copy)]
+pub struct AicStep {
+    /// the most links a chain may have
+    pub max_length: usize,
+}
+
+impl Default for AicStep {
+    fn default() -> Self {
+        AicStep {
+            max_length: MAX_LENGTH,
+        }
+    }
+}
+
+impl AicStep {
+    fn find(&self, candidates: &Candidates) -> Option<Found> {
+        open_chain(candidates, &Graph::new(candidates, false), self.max_length)
+    }
+}
+
+impl SolverStep for AicStep {
+    fn name(&self) -> &'static str {
+        "AIC"
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        self.find(candidates)
+            .is_some_and(|found| found.apply(candidates))
+    }
+
+    fn chain(&self, candidates: &Candidates) -> Option<Chain> {
+        self.find(candidates).and_then(|found| found.chain)
+    }
+}
+
+/// a chain that returns to where it started.
+///
+/// a continuous loop turns every weak link into a strong one, so whatever sees both ends of a
+/// weak link of the loop is false. a discontinuous loop meets itself in a candidate with two
+/// strong links, which is true, or with two weak links, which is false.
+#[derive(Debug, Clone, Copy)]
+pub struct NiceLoopStep {
+    /// the most links a loop may have
+    pub max_length: usize,
+}
+
+impl Default for NiceLoopStep {
+    fn default() -> Self {
+        NiceLoopStep {
+            max_length: MAX_LENGTH,
+        }
+    }
+}
+
+impl NiceLoopStep {
+    fn find(&self, candidates: &Candidates) -> Option<Found> {
+        let graph = Graph::new(candidates, false);
+        let sees = |a: usize, b: usize| graph.weak[a].contains(&b);
+
+        graph.nodes.iter().find_map(|&start| {
+            // starting with the candidate being false
+            search(
+                &graph,
+                start,
+                Link::Strong,
+                self.max_length.saturating_sub(1),
+                |path| {
+                    let end = *path.last().unwrap();
+                    if path.len() < 4 {
+                        None
+                    } else if end == start {
+                        Some(Found {
+                            placements: vec![start],
+                            eliminations: vec![],
+                            chain: Some(to_chain(path, alternating(path, Link::Strong))),
+                            branches: vec![],
+                            pattern: vec![],
+                        })
+                    } else if sees(end, start) {
+                        let mut nodes = path.to_vec();
+                        nodes.push(start);
+                        let mut eliminations = vec![];
+                        for weak in nodes.windows(2).skip(1).step_by(2) {
+                            for n in seen_by_both(candidates, weak[0], weak[1]) {
+                                if !nodes.contains(&n) && !eliminations.contains(&n) {
+                                    eliminations.push(n);
+                                }
+                            }
+                        }
+                        (!eliminations.is_empty()).then(|| Found {
+                            placements: vec![],
+                            eliminations,
+                            chain: Some(to_chain(&nodes, alternating(&nodes, Link::Strong))),
+                            branches: vec![],
+                            pattern: vec![],
+                        })
+                    } else {
+                        None
+                    }
+                },
+            )
+            // then with it being true
+            .or_else(|| {
+                search(
+                    &graph,
+                    start,
+                    Link::Weak,
+                    self.max_length.saturating_sub(1),
+                    |path| {
+                        let end = *path.last().unwrap();
+                        (path.len() >= 5 && end != start && sees(end, start)).then(|| {
+                            let mut nodes = path.to_vec();
+                            nodes.push(start);
+                            Found {
+                                placements: vec![],
+                                eliminations: vec![start],
+                                chain: Some(to_chain(&nodes, alternating(&nodes, Link::Weak))),
+                                branches: vec![],
+                                pattern: vec![],
+                            }
+                        })
+                    },
+                )
+            })
+        })
+    }
+}
+
+impl SolverStep for NiceLoopStep {
+    fn name(&self) -> &'static str {
+        "Nice Loop"
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        self.find(candidates)
+            .is_some_and(|found| found.apply(candidates))
+    }
+
+    fn chain(&self, candidates: &Candidates) -> Option<Chain> {
+        self.find(candidates).and_then(|found| found.chain)
+    }
+}
+
+/// everything that follows from assuming one candidate is true, up to a number of links
+struct Implications {
+    /// the candidate each conclusion follows from, by whether the conclusion is that it is
+    /// false or true
+    parent: [Vec<Option<usize>>; 2],
+    reached: [Vec<bool>; 2],
+}
+
+impl Implications {
+    fn new(graph: &Graph, assumption: usize, max_length: usize) -> Self {
+        let mut implications = Implications {
+            parent: [vec![None; 81 * 9], vec![None; 81 * 9]],
+            reached: [vec![false; 81 * 9], vec![false; 81 * 9]],
+        };
+        implications.reached[1][assumption] = true;
+
+        let mut queue = VecDeque::from([(assumption, 1, 0)]);
+        while let Some((at, value, length)) = queue.pop_front() {
+            if length == max_length {
+                continue;
+            }
+
+            // a true candidate makes the weakly linked ones false, a false one makes the
+            // strongly linked ones true
+            let next = if value == 1 {
+                &graph.weak[at]
+            } else {
+                &graph.strong[at]
+            };
+            for &to in next {
+                let to_value = 1 - value;
+                if !implications.reached[to_value][to] {
+                    implications.reached[to_value][to] = true;
+                    implications.parent[to_value][to] = Some(at);
+                    queue.push_back((to, to_value, length + 1));
+                }
+            }
+        }
+
+        implications
+    }
+
+    /// the chain from the assumption to the conclusion that `node` has `value`
+    fn chain_to(&self, node: usize, value: usize) -> Chain {
+        let mut nodes = vec![node];
+        let (mut at, mut value) = (node, value);
+        while let Some(previous) = self.parent[value][at] {
+            nodes.push(previous);
+            (at, value) = (previous, 1 - value);
+        }
+        nodes.reverse();
+        let links = alternating(&nodes, Link::Weak);
+        to_chain(&nodes, links)
+    }
+}
+
+/// the first conclusion all of the assumptions lead to: a candidate that is true in every case,
+/// or one that is false in every case
+fn forcing(
+    graph: &Graph,
+    assumptions: &[usize],
+    max_length: usize,
+) -> Option<(usize, usize, Vec<Chain>)> {
+    let branches: Vec<Implications> = assumptions
+        .iter()
+        .map(|&assumption| Implications::new(graph, assumption, max_length))
+        .collect();
+
+    for value in [1, 0] {
+        for &n in &graph.nodes {
+            if !assumptions.contains(&n) && branches.iter().all(|branch| branch.reached[value][n]) {
+                let chains = branches
+                    .iter()
+                    .map(|branch| branch.chain_to(n, value))
+                    .collect();
+                return Some((n, value, chains));
+            }
+        }
+    }
+
+    None
+}
+
+fn forcing_found(
+    source: PatternPart,
+    conclusion: Option<(usize, usize, Vec<Chain>)>,
+) -> Option<Found> {
+    conclusion.map(|(n, value, branches)| Found {
+        placements: if value == 1 { vec![n] } else { vec![] },
+        eliminations: if value == 0 { vec![n] } else { vec![] },
+        chain: None,
+        branches,
+        pattern: vec![source],
+    })
+}
+
+/// whatever follows from every candidate of a cell being true
+#[derive(Debug, Clone, Copy)]
+pub struct CellForcingChainStep {
+    /// the most links each branch may have
+    pub max_length: usize,
+}
+
+impl Default for CellForcingChainStep {
+    fn default() -> Self {
+        CellForcingChainStep {
+            max_length: MAX_LENGTH,
+        }
+    }
+}
+
+impl CellForcingChainStep {
+    fn find(&self, candidates: &Candidates) -> Option<Found> {
+        let graph = Graph::new(candidates, false);
+        candidates.unsolved().iter().find_map(|cell| {
+            let notes = candidates.cell(cell);
+            let assumptions: Vec<usize> = digits(notes).map(|digit| node(cell, digit)).collect();
+            if assumptions.len() < 2 {
+                return None;
+            }
+
+            forcing_found(
+                PatternPart::new("cell", Bitboard::cell(cell), notes),
+                forcing(&graph, &assumptions, self.max_length),
+            )
+        })
+    }
+}
+
+impl SolverStep for CellForcingChainStep {
+    fn name(&self) -> &'static str {
+        "Cell Forcing Chain"
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        self.find(candidates)
+            .is_some_and(|found| found.apply(candidates))
+    }
+
+    fn branches(&self, candidates: &Candidates) -> Vec<Chain> {
+        self.find(candidates).map_or(vec![], |found| found.branches)
+    }
+
+    fn pattern(&self, candidates: &Candidates) -> Vec<PatternPart> {
+        self.find(candidates).map_or(vec![], |found| found.pattern)
+    }
+}
+
+/// whatever follows from every place of a digit in a house holding it
+#[derive(Debug, Clone, Copy)]
+pub struct UnitForcingChainStep {
+    /// the most links each branch may have
+    pub max_length: usize,
+}
+
+impl Default for UnitForcingChainStep {
+    fn default() -> Self {
+        UnitForcingChainStep {
+            max_length: MAX_LENGTH,
+        }
+    }
+}
+
+impl UnitForcingChainStep {
+    fn find(&self, candidates: &Candidates) -> Option<Found> {
+        let graph = Graph::new(candidates, false);
+        HOUSES.iter().find_map(|&house| {
+            (1..=9u8).find_map(|digit| {
+                let places = candidates.digit(digit) & house;
+                if places.count() < 2 {
+                    return None;
+                }
+
+                let assumptions: Vec<usize> = places.iter().map(|cell| node(cell, digit)).collect();
+                forcing_found(
+                    PatternPart::new("house", places, 1 << (digit - 1)),
+                    forcing(&graph, &assumptions, self.max_length),
+                )
+            })
+        })
+    }
+}
+
+impl SolverStep for UnitForcingChainStep {
+    fn name(&self) -> &'static str {
+        "Unit Forcing Chain"
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        self.find(candidates)
+            .is_some_and(|found| found.apply(candidates))
+    }
+
+    fn branches(&self, candidates: &Candidates) -> Vec<Chain> {
+        self.find(candidates).map_or(vec![], |found| found.branches)
+    }
+
+    fn pattern(&self, candidates: &Candidates) -> Vec<PatternPart> {
+        self.find(candidates).map_or(vec![], |found| found.pattern)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sudoku::Numbers;
+    use crate::techniques::test_util::{cells, eliminated, with_notes};
+
+    #[test]
+    fn finds_xy_chain() {
+        let candidates = with_notes(&[
+            (0, 0, &[1, 2]),
+            (0, 4, &[2, 3]),
+            (4, 4, &[3, 4]),
+            (4, 8, &[1, 4]),
+        ]);
+        let step = XyChainStep::default();
+        assert_eq!(cells(&[(0, 8), (4, 0)]), eliminated(&step, &candidates, 1));
+        assert_eq!(
+            "(1)r1c1=(2)r1c1-r1c5=(3)r1c5-r5c5=(4)r5c5-r5c9=(1)r5c9",
+            step.chain(&candidates).unwrap().to_string()
+        );
+
+        // the chain has seven links
+        let short = XyChainStep { max_length: 6 };
+        assert!(!short.apply_candidates(&mut candidates.clone()));
+    }
+
+    #[test]
+    fn finds_aic_with_strong_links_in_houses() {
+        // a skyscraper on 1 in the first and fifth row
+        let mut candidates = Candidates::from_numbers(&Numbers::empty());
+        candidates.eliminate(1, HOUSES[0] & !cells(&[(0, 1), (0, 4)]));
+        candidates.eliminate(1, HOUSES[4] & !cells(&[(4, 1), (4, 5)]));
+        assert_eq!(
+            cells(&[(1, 5), (2, 5), (3, 4), (5, 4)]),
+            eliminated(&AicStep::default(), &candidates, 1)
+        );
+        assert!(!XyChainStep::default().apply_candidates(&mut candidates.clone()));
+
+        // one of the two places in the first row has to hold the 1 either way
+        let step = UnitForcingChainStep::default();
+        assert_eq!(cells(&[(1, 5)]), eliminated(&step, &candidates, 1));
+        assert_eq!(2, step.branches(&candidates).len());
+    }
+
+    #[test]
+    fn finds_continuous_nice_loop() {
+        let candidates = with_notes(&[
+            (0, 0, &[1, 2]),
+            (0, 4, &[2, 3]),
+            (4, 4, &[3, 4]),
+            (4, 0, &[1, 4]),
+        ]);
+        let step = NiceLoopStep::default();
+        assert_eq!(
+            "(1)r1c1=(2)r1c1-r1c5=(3)r1c5-r5c5=(4)r5c5-r5c1=(1)r5c1-r1c1",
+            step.chain(&candidates).unwrap().to_string()
+        );
+
+        // every weak link of the loop becomes strong
+        let mut after = candidates;
+        assert!(step.apply_candidates(&mut after));
+        for (digit, house) in [(2, 0), (3, 13), (4, 4), (1, 9)] {
+            assert_eq!(2, (after.digit(digit) & HOUSES[house]).count());
+        }
+    }
+
+    #[test]
+    fn finds_discontinuous_nice_loop() {
+        // assuming r1c1 isn't 1 leads to it being 1
+        let mut candidates = with_notes(&[(0, 0, &[1, 2]), (0, 4, &[2, 3]), (4, 4, &[3, 1])]);
+        candidates.eliminate(1, HOUSES[4] & !cells(&[(4, 0), (4, 4)]));
+        candidates.eliminate(1, HOUSES[9] & !cells(&[(0, 0), (4, 0)]));
+        let mut after = candidates;
+        assert!(NiceLoopStep::default().apply_candidates(&mut after));
+        assert_eq!(1, after.value(0));
+    }
+
+    #[test]
+    fn finds_cell_forcing_chain() {
+        // an xy-wing seen from one of its pincers
+        let candidates = with_notes(&[(4, 4, &[1, 2]), (4, 0, &[1, 3]), (0, 4, &[2, 3])]);
+        let step = CellForcingChainStep::default();
+        assert_eq!(cells(&[(0, 0)]), eliminated(&step, &candidates, 3));
+
+        let mut solver = crate::solver::Solver::default();
+        solver.add_step(step);
+        let log =
+            solver.solve_log(candidates.apply_to(crate::sudoku::Game::create(Numbers::empty())));
+        assert_eq!(
+            "Cell Forcing Chain: cell r1c5 {23}; (2)r1c5-r5c5=(1)r5c5-r5c1=(3)r5c1-r1c1 | \
+             (3)r1c5-r1c1; eliminates r1c1<>3",
+            log.entries[0].explanation
+        );
+    }
+}
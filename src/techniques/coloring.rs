@@ -0,0 +1,329 @@
+//! coloring: the cells of a digit that are joined by strong links (conjugate pairs) form
+//! clusters, whose cells alternate between two colors. one of the colors holds the digit in
+//! all of its cells, the other in none.
+//!
+//! the colors are reported as the pattern parts "color 1" and "color 2", and for a second
+//! cluster "color 3" and "color 4", so they can be shown on the grid.
+
+use crate::bitboard::{Bitboard, Candidates, HOUSES, PEERS};
+use crate::solver::{PatternPart, SolverStep};
+
+/// the clusters of the cells joined by strong links, each split into its two colors
+fn clusters(cells: Bitboard) -> Vec<[Bitboard; 2]> {
+    let mut links = [Bitboard::EMPTY; 81];
+    for house in HOUSES {
+        let pair = cells & house;
+        if pair.count() == 2 {
+            let mut ends = pair.iter();
+            let (a, b) = (ends.next().unwrap(), ends.next().unwrap());
+            links[a].insert(b);
+            links[b].insert(a);
+        }
+    }
+
+    let mut clusters = vec![];
+    let mut seen = Bitboard::EMPTY;
+    for start in cells {
+        if seen.contains(start) || links[start].is_empty() {
+            continue;
+        }
+
+        let mut colors = [Bitboard::cell(start), Bitboard::EMPTY];
+        let mut stack = vec![(start, 0)];
+        seen.insert(start);
+        while let Some((cell, color)) = stack.pop() {
+            for next in links[cell] {
+                if !seen.contains(next) {
+                    seen.insert(next);
+                    colors[1 - color].insert(next);
+                    stack.push((next, 1 - color));
+                }
+            }
+        }
+        clusters.push(colors);
+    }
+
+    clusters
+}
+
+/// the cells that see at least one of the cells
+fn seen_by_any(cells: Bitboard) -> Bitboard {
+    cells
+        .iter()
+        .fold(Bitboard::EMPTY, |seen, cell| seen | PEERS[cell])
+}
+
+/// the eliminations of one digit, with the colors behind them
+struct Found {
+    digit: u8,
+    eliminations: Bitboard,
+    colors: Vec<Bitboard>,
+}
+
+impl Found {
+    fn pattern(self) -> Vec<PatternPart> {
+        self.colors
+            .iter()
+            .enumerate()
+            .map(|(i, &cells)| {
+                PatternPart::new(&format!("color {}", i + 1), cells, 1 << (self.digit - 1))
+            })
+            .collect()
+    }
+
+    fn apply(self, candidates: &mut Candidates) -> bool {
+        candidates.eliminate(self.digit, self.eliminations)
+    }
+}
+
+/// the first cluster of any digit for which `find` returns eliminations
+fn find_cluster(
+    candidates: &Candidates,
+    find: impl Fn(Bitboard, [Bitboard; 2]) -> Bitboard,
+) -> Option<Found> {
+    (1..=9u8).find_map(|digit| {
+        let cells = candidates.digit(digit);
+        clusters(cells).into_iter().find_map(|colors| {
+            let eliminations = find(cells, colors);
+            (!eliminations.is_empty()).then(|| Found {
+                digit,
+                eliminations,
+                colors: colors.to_vec(),
+            })
+        })
+    })
+}
+
+/// two cells of the same color see each other, so that color can't hold the digit anywhere
+#[derive(Debug)]
+pub struct ColorWrapStep;
+
+fn color_wrap(candidates: &Candidates) -> Option<Found> {
+    find_cluster(candidates, |_, colors| {
+        colors
+            .into_iter()
+            .find(|&color| !(seen_by_any(color) & color).is_empty())
+            .unwrap_or(Bitboard::EMPTY)
+    })
+}
+
+impl SolverStep for ColorWrapStep {
+    fn name(&self) -> &'static str {
+        "Color Wrap"
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        color_wrap(candidates).is_some_and(|found| found.apply(candidates))
+    }
+
+    fn pattern(&self, candidates: &Candidates) -> Vec<PatternPart> {
+        color_wrap(candidates).map_or(vec![], Found::pattern)
+    }
+}
+
+/// a cell outside the cluster sees both colors, so whichever holds the digit takes it away
+#[derive(Debug)]
+pub struct ColorTrapStep;
+
+fn color_trap(candidates: &Candidates) -> Option<Found> {
+    find_cluster(candidates, |cells, [first, second]| {
+        cells & !first & !second & seen_by_any(first) & seen_by_any(second)
+    })
+}
+
+impl SolverStep for ColorTrapStep {
+    fn name(&self) -> &'static str {
+        "Color Trap"
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        color_trap(candidates).is_some_and(|found| found.apply(candidates))
+    }
+
+    fn pattern(&self, candidates: &Candidates) -> Vec<PatternPart> {
+        color_trap(candidates).map_or(vec![], Found::pattern)
+    }
+}
+
+/// two clusters of the same digit where a color of one sees a color of the other: they can't
+/// both hold the digit, so one of their opposite colors does. and a color that sees both
+/// colors of the other cluster can't hold the digit at all.
+#[derive(Debug)]
+pub struct MultiColoringStep;
+
+fn multi_coloring(candidates: &Candidates) -> Option<Found> {
+    (1..=9u8).find_map(|digit| {
+        let cells = candidates.digit(digit);
+        let clusters = clusters(cells);
+        clusters.iter().enumerate().find_map(|(i, &first)| {
+            clusters.iter().enumerate().find_map(|(j, &second)| {
+                if i == j {
+                    return None;
+                }
+
+                for a in 0..2 {
+                    let sees = seen_by_any(first[a]);
+                    let eliminations =
+                        if !(sees & second[0]).is_empty() && !(sees & second[1]).is_empty() {
+                            first[a]
+                        } else if let Some(b) = (0..2).find(|&b| !(sees & second[b]).is_empty()) {
+                            cells
+                                & seen_by_any(first[1 - a])
+                                & seen_by_any(second[1 - b])
+                                & !first[1 - a]
+                                & !second[1 - b]
+                        } else {
+                            Bitboard::EMPTY
+                        };
+
+                    if !eliminations.is_empty() {
+                        return Some(Found {
+                            digit,
+                            eliminations,
+                            colors: vec![first[0], first[1], second[0], second[1]],
+                        });
+                    }
+                }
+
+                None
+            })
+        })
+    })
+}
+
+impl SolverStep for MultiColoringStep {
+    fn name(&self) -> &'static str {
+        "Multi-Coloring"
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        multi_coloring(candidates).is_some_and(|found| found.apply(candidates))
+    }
+
+    fn pattern(&self, candidates: &Candidates) -> Vec<PatternPart> {
+        multi_coloring(candidates).map_or(vec![], Found::pattern)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::techniques::fish::FishStep;
+    use crate::techniques::intersections::{ClaimingStep, PointingStep};
+    use crate::techniques::single_digit::{
+        EmptyRectangleStep, SkyscraperStep, TurbotFishStep, TwoStringKiteStep,
+    };
+    use crate::techniques::test_util::{cells, eliminated, restricted};
+
+    const DIGIT: u8 = 1;
+
+    /// the steps tried before coloring make no progress, so the position needs it
+    fn assert_needs_coloring(candidates: &Candidates) {
+        let simpler: [&dyn SolverStep; 9] = [
+            &PointingStep,
+            &ClaimingStep,
+            &FishStep(2),
+            &FishStep(3),
+            &FishStep(4),
+            &SkyscraperStep,
+            &TwoStringKiteStep,
+            &TurbotFishStep,
+            &EmptyRectangleStep,
+        ];
+        for step in simpler {
+            assert!(
+                !step.apply_candidates(&mut candidates.clone()),
+                "{}",
+                step.name()
+            );
+        }
+    }
+
+    #[test]
+    fn finds_color_wrap() {
+        // r1c1 and r2c2 share a box and a color, six links apart
+        let candidates = restricted(
+            DIGIT,
+            &[
+                (0, &[(0, 0), (0, 4)]),
+                (13, &[(0, 4), (3, 4)]),
+                (3, &[(3, 4), (3, 7)]),
+                (16, &[(3, 7), (7, 7)]),
+                (7, &[(7, 7), (7, 1)]),
+                (10, &[(7, 1), (1, 1)]),
+            ],
+        );
+        assert_needs_coloring(&candidates);
+        assert_eq!(
+            cells(&[(0, 0), (1, 1), (3, 4), (7, 7)]),
+            eliminated(&ColorWrapStep, &candidates, DIGIT)
+        );
+        assert_eq!(
+            vec![
+                "color 1 r1c1|r2c2|r4c5|r8c8 {1}",
+                "color 2 r1c5|r4c8|r8c2 {1}"
+            ],
+            ColorWrapStep
+                .pattern(&candidates)
+                .iter()
+                .map(|part| part.to_string())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn finds_color_trap() {
+        // only the ends of the five links, r1c1 and r8c2, are seen together
+        let candidates = restricted(
+            DIGIT,
+            &[
+                (0, &[(0, 0), (0, 4)]),
+                (13, &[(0, 4), (3, 4)]),
+                (3, &[(3, 4), (3, 7)]),
+                (16, &[(3, 7), (7, 7)]),
+                (7, &[(7, 7), (7, 1)]),
+            ],
+        );
+        assert_needs_coloring(&candidates);
+        assert!(!ColorWrapStep.apply_candidates(&mut candidates.clone()));
+        assert_eq!(
+            cells(&[(1, 1), (2, 1), (6, 0), (8, 0)]),
+            eliminated(&ColorTrapStep, &candidates, DIGIT)
+        );
+    }
+
+    #[test]
+    fn finds_multi_coloring() {
+        // r8c5 sees r7c4, so r1c5 or r6c9 holds the digit and r1c9 sees both
+        let candidates = restricted(
+            DIGIT,
+            &[
+                (19, &[(0, 4), (1, 5)]),
+                (13, &[(0, 4), (7, 4)]),
+                (6, &[(6, 3), (6, 0)]),
+                (9, &[(6, 0), (5, 0)]),
+                (5, &[(5, 0), (5, 8)]),
+            ],
+        );
+        assert_needs_coloring(&candidates);
+        assert!(!ColorWrapStep.apply_candidates(&mut candidates.clone()));
+        assert!(!ColorTrapStep.apply_candidates(&mut candidates.clone()));
+        assert_eq!(
+            cells(&[(0, 8)]),
+            eliminated(&MultiColoringStep, &candidates, DIGIT)
+        );
+        assert_eq!(
+            vec![
+                "color 1 r1c5 {1}",
+                "color 2 r2c6|r8c5 {1}",
+                "color 3 r6c1|r7c4 {1}",
+                "color 4 r6c9|r7c1 {1}"
+            ],
+            MultiColoringStep
+                .pattern(&candidates)
+                .iter()
+                .map(|part| part.to_string())
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,124 @@
+//! fish: a digit whose places in `n` rows (the base) lie in only `n` columns (the cover). the
+//! digit is once in each base row, so it fills each cover column there, and it is gone from
+//! the rest of the cover. the same holds with rows and columns swapped.
+
+use crate::bitboard::{houses_of, Bitboard, Candidates, HOUSES};
+use crate::solver::{PatternPart, SolverStep};
+use crate::techniques::subsets;
+
+/// a digit on `size` base lines that lie in `size` cover lines. the size is 2 (x-wing), 3
+/// (swordfish) or 4 (jellyfish).
+#[derive(Debug)]
+pub struct FishStep(pub usize);
+
+/// the places of the digit in the base lines, and where it is removed
+struct Fish {
+    digit: u8,
+    cells: Bitboard,
+    eliminations: Bitboard,
+}
+
+fn fish(candidates: &Candidates, size: usize) -> Option<Fish> {
+    for digit in 1..=9u8 {
+        let places = candidates.digit(digit);
+        // rows as the base and columns as the cover, then the other way around
+        for (base, cover) in [(0, 1), (1, 0)] {
+            let lines: Vec<usize> = (base * 9..base * 9 + 9)
+                .filter(|&line| (2..=size).contains(&((places & HOUSES[line]).count() as usize)))
+                .collect();
+
+            let found = subsets(&lines, size, &mut vec![], &mut |chosen| {
+                let cells = chosen.iter().fold(Bitboard::EMPTY, |cells, &line| {
+                    cells | (places & HOUSES[line])
+                });
+                let covers = cells
+                    .iter()
+                    .fold(0u32, |covers, cell| covers | 1 << houses_of(cell)[cover]);
+                if covers.count_ones() as usize != size {
+                    return None;
+                }
+
+                let eliminations = (0..18)
+                    .filter(|&line| covers & (1 << line) != 0)
+                    .fold(Bitboard::EMPTY, |all, line| all | HOUSES[line])
+                    & places
+                    & !cells;
+                (!eliminations.is_empty()).then_some(Fish {
+                    digit,
+                    cells,
+                    eliminations,
+                })
+            });
+            if found.is_some() {
+                return found;
+            }
+        }
+    }
+
+    None
+}
+
+impl SolverStep for FishStep {
+    fn name(&self) -> &'static str {
+        match self.0 {
+            2 => "X-Wing",
+            3 => "Swordfish",
+            _ => "Jellyfish",
+        }
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        fish(candidates, self.0)
+            .is_some_and(|fish| candidates.eliminate(fish.digit, fish.eliminations))
+    }
+
+    fn pattern(&self, candidates: &Candidates) -> Vec<PatternPart> {
+        fish(candidates, self.0).map_or(vec![], |fish| {
+            vec![PatternPart::new("fish", fish.cells, 1 << (fish.digit - 1))]
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sudoku::Numbers;
+
+    /// a grid where every cell has all candidates, except that 1 is only in the given
+    /// `(row, col)` cells of the given rows
+    fn fish_rows(cells: &[(usize, usize)]) -> Candidates {
+        let mut candidates = Candidates::from_numbers(&Numbers::empty());
+        for &(row, _) in cells {
+            let allowed = cells
+                .iter()
+                .filter(|&&(r, _)| r == row)
+                .fold(Bitboard::EMPTY, |all, &(r, c)| {
+                    all | Bitboard::cell(r * 9 + c)
+                });
+            candidates.eliminate(1, HOUSES[row] & !allowed);
+        }
+
+        candidates
+    }
+
+    #[test]
+    fn finds_x_wing() {
+        let candidates = fish_rows(&[(1, 2), (1, 6), (5, 2), (5, 6)]);
+        let mut after = candidates;
+        assert!(FishStep(2).apply_candidates(&mut after));
+        assert_eq!(
+            (HOUSES[11] | HOUSES[15]) & !HOUSES[1] & !HOUSES[5],
+            candidates.digit(1) & !after.digit(1)
+        );
+    }
+
+    #[test]
+    fn finds_swordfish() {
+        let candidates = fish_rows(&[(0, 0), (0, 4), (3, 4), (3, 8), (7, 0), (7, 8)]);
+        assert!(!FishStep(2).apply_candidates(&mut candidates.clone()));
+        let mut after = candidates;
+        assert!(FishStep(3).apply_candidates(&mut after));
+        assert!(!after.digit(1).contains(4 * 9 + 8));
+        assert!(after.digit(1).contains(4 * 9 + 7));
+    }
+}
@@ -0,0 +1,298 @@
+//! intersections: the cells a box shares with a row or column see both houses, so a digit that
+//! is locked into them by one of the houses is gone from the rest of the other.
+
+use crate::bitboard::{Bitboard, Candidates, HOUSES};
+use crate::solver::{PatternPart, SolverStep};
+use crate::techniques::count;
+
+/// every subset of the cells, with the union of their notes
+fn cell_sets(candidates: &Candidates, cells: Bitboard) -> Vec<(Bitboard, u16)> {
+    let cells: Vec<usize> = cells.iter().collect();
+    let mut subsets = vec![(Bitboard::EMPTY, 0u16); 1 << cells.len()];
+    for mask in 1..subsets.len() {
+        let lowest = mask.trailing_zeros() as usize;
+        let (set, notes) = subsets[mask & (mask - 1)];
+        subsets[mask] = (
+            set | Bitboard::cell(cells[lowest]),
+            notes | candidates.cell(cells[lowest]),
+        );
+    }
+
+    subsets.remove(0);
+    subsets
+}
+
+/// a digit locked into an intersection, and where it is removed
+struct Locked {
+    digit: u8,
+    cells: Bitboard,
+    eliminations: Bitboard,
+}
+
+/// the first digit whose places in a house of `from` all lie in one house of `to`
+fn locked(candidates: &Candidates, from: &[Bitboard], to: &[Bitboard]) -> Option<Locked> {
+    for digit in 1..=9u8 {
+        for &house in from {
+            let cells = candidates.digit(digit) & house;
+            if cells.count() < 2 {
+                continue;
+            }
+
+            for &other in to {
+                let eliminations = candidates.digit(digit) & other & !house;
+                if (cells & !other).is_empty() && !eliminations.is_empty() {
+                    return Some(Locked {
+                        digit,
+                        cells,
+                        eliminations,
+                    });
+                }
+            }
+        }
+    }
+
+    None
+}
+
+impl Locked {
+    fn pattern(self) -> Vec<PatternPart> {
+        vec![PatternPart::new(
+            "intersection",
+            self.cells,
+            1 << (self.digit - 1),
+        )]
+    }
+}
+
+/// the places of a digit in a box all lie in one row or column, so it is gone from the rest of
+/// that line
+#[derive(Debug)]
+pub struct PointingStep;
+
+impl SolverStep for PointingStep {
+    fn name(&self) -> &'static str {
+        "Pointing"
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        locked(candidates, &HOUSES[18..], &HOUSES[..18])
+            .is_some_and(|found| candidates.eliminate(found.digit, found.eliminations))
+    }
+
+    fn pattern(&self, candidates: &Candidates) -> Vec<PatternPart> {
+        locked(candidates, &HOUSES[18..], &HOUSES[..18]).map_or(vec![], Locked::pattern)
+    }
+}
+
+/// the places of a digit in a row or column all lie in one box, so it is gone from the rest of
+/// that box
+#[derive(Debug)]
+pub struct ClaimingStep;
+
+impl SolverStep for ClaimingStep {
+    fn name(&self) -> &'static str {
+        "Claiming"
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        locked(candidates, &HOUSES[..18], &HOUSES[18..])
+            .is_some_and(|found| candidates.eliminate(found.digit, found.eliminations))
+    }
+
+    fn pattern(&self, candidates: &Candidates) -> Vec<PatternPart> {
+        locked(candidates, &HOUSES[..18], &HOUSES[18..]).map_or(vec![], Locked::pattern)
+    }
+}
+
+/// the pattern of a sue de coq and the digits it removes from the rest of the line and box
+struct SueDeCoq {
+    parts: Vec<PatternPart>,
+    eliminations: Vec<(u8, Bitboard)>,
+}
+
+impl SueDeCoq {
+    fn apply(self, candidates: &mut Candidates) -> bool {
+        self.eliminations
+            .into_iter()
+            .fold(false, |changed, (digit, cells)| {
+                candidates.eliminate(digit, cells) | changed
+            })
+    }
+}
+
+/// two or more cells of an intersection holding at least two more candidates than cells, with
+/// cells of the line and cells of the box that have no candidate in common, and as many
+/// candidates between all of them as there are cells.
+///
+/// no digit can be twice in these cells, since two places of one digit would need a cell of
+/// the line and a cell of the box that share it. so every digit is in them exactly once: the
+/// candidates of the line cells, and those of the intersection that the box cells lack, are
+/// gone from the rest of the line, and the other way around for the box.
+///
+/// the line and box cells may also hold candidates the intersection lacks, which covers the
+/// extended forms.
+#[derive(Debug)]
+pub struct SueDeCoqStep;
+
+fn sue_de_coq(candidates: &Candidates) -> Option<SueDeCoq> {
+    let unsolved = candidates.unsolved();
+    for &b in &HOUSES[18..] {
+        for &line in &HOUSES[..18] {
+            let intersection = b & line & unsolved;
+            if intersection.count() < 2 {
+                continue;
+            }
+
+            let line_rest = cell_sets(candidates, line & !b & unsolved);
+            let box_rest = cell_sets(candidates, b & !line & unsolved);
+            for (cells, notes) in cell_sets(candidates, intersection) {
+                if cells.count() < 2 || count(notes) < cells.count() as usize + 2 {
+                    continue;
+                }
+
+                let useful =
+                    |set: Bitboard| set.iter().all(|cell| candidates.cell(cell) & notes != 0);
+                for &(line_cells, line_notes) in &line_rest {
+                    if !useful(line_cells) {
+                        continue;
+                    }
+
+                    for &(box_cells, box_notes) in &box_rest {
+                        let size = (cells | line_cells | box_cells).count() as usize;
+                        if line_notes & box_notes != 0
+                            || count(notes | line_notes | box_notes) != size
+                            || !useful(box_cells)
+                        {
+                            continue;
+                        }
+
+                        let line_digits = line_notes | (notes & !box_notes);
+                        let box_digits = box_notes | (notes & !line_notes);
+                        let pattern = cells | line_cells | box_cells;
+                        let eliminations: Vec<(u8, Bitboard)> = (1..=9u8)
+                            .map(|digit| {
+                                let bit = 1 << (digit - 1);
+                                let mut houses = Bitboard::EMPTY;
+                                if line_digits & bit != 0 {
+                                    houses |= line;
+                                }
+                                if box_digits & bit != 0 {
+                                    houses |= b;
+                                }
+                                (digit, houses & !pattern & candidates.digit(digit))
+                            })
+                            .filter(|(_, cells)| !cells.is_empty())
+                            .collect();
+                        if eliminations.is_empty() {
+                            continue;
+                        }
+
+                        return Some(SueDeCoq {
+                            parts: vec![
+                                PatternPart::new("intersection", cells, notes),
+                                PatternPart::new("line", line_cells, line_notes),
+                                PatternPart::new("box", box_cells, box_notes),
+                            ],
+                            eliminations,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    None
+}
+
+impl SolverStep for SueDeCoqStep {
+    fn name(&self) -> &'static str {
+        "Sue de Coq"
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        sue_de_coq(candidates).is_some_and(|found| found.apply(candidates))
+    }
+
+    fn pattern(&self, candidates: &Candidates) -> Vec<PatternPart> {
+        sue_de_coq(candidates).map_or(vec![], |found| found.parts)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::techniques::test_util::{cells, with_notes};
+
+    #[test]
+    fn finds_pointing() {
+        let mut candidates = with_notes(&[]);
+        candidates.eliminate(5, HOUSES[18] & !cells(&[(0, 0), (0, 2)]));
+        assert!(!ClaimingStep.apply_candidates(&mut candidates.clone()));
+
+        let mut after = candidates;
+        assert!(PointingStep.apply_candidates(&mut after));
+        assert_eq!(
+            HOUSES[0] & !HOUSES[18],
+            candidates.digit(5) & !after.digit(5)
+        );
+    }
+
+    #[test]
+    fn finds_claiming() {
+        let mut candidates = with_notes(&[]);
+        candidates.eliminate(5, HOUSES[0] & !cells(&[(0, 0), (0, 2)]));
+        assert!(!PointingStep.apply_candidates(&mut candidates.clone()));
+
+        let mut after = candidates;
+        assert!(ClaimingStep.apply_candidates(&mut after));
+        assert_eq!(
+            HOUSES[18] & !HOUSES[0],
+            candidates.digit(5) & !after.digit(5)
+        );
+    }
+
+    #[test]
+    fn finds_sue_de_coq() {
+        let candidates = with_notes(&[
+            (0, 0, &[1, 2, 3]),
+            (0, 1, &[1, 2, 4]),
+            (0, 4, &[1, 2]),
+            (1, 0, &[3, 4]),
+        ]);
+        let mut after = candidates;
+        assert!(SueDeCoqStep.apply_candidates(&mut after));
+
+        let line = cells(&[(0, 2), (0, 3), (0, 5), (0, 6), (0, 7), (0, 8)]);
+        let b = cells(&[(0, 2), (1, 1), (1, 2), (2, 0), (2, 1), (2, 2)]);
+        for (digit, cells) in [(1, line), (2, line), (3, b), (4, b)] {
+            assert_eq!(cells, candidates.digit(digit) & !after.digit(digit));
+        }
+        for digit in 5..=9 {
+            assert_eq!(candidates.digit(digit), after.digit(digit));
+        }
+
+        assert_eq!(
+            vec![
+                "intersection r1c12 {1234}",
+                "line r1c5 {12}",
+                "box r2c1 {34}"
+            ],
+            SueDeCoqStep
+                .pattern(&candidates)
+                .iter()
+                .map(|part| part.to_string())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn ignores_line_and_box_cells_sharing_a_candidate() {
+        let candidates = with_notes(&[
+            (0, 0, &[1, 2, 3]),
+            (0, 1, &[1, 2, 4]),
+            (0, 4, &[1, 2]),
+            (1, 0, &[2, 3, 4]),
+        ]);
+        assert!(!SueDeCoqStep.apply_candidates(&mut candidates.clone()));
+    }
+}
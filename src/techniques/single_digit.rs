@@ -0,0 +1,448 @@
+//! patterns of a single digit, built from strong links (a house where the digit has only two
+//! cells left, so one of them holds it) and weak links (two cells that see each other, so at
+//! most one of them holds it).
+//!
+//! a chain that starts and ends with a strong link proves that one of its ends holds the digit,
+//! so the digit can be removed from every cell that sees both ends.
+
+use crate::bitboard::{houses_of, Bitboard, Candidates, HOUSES, PEERS};
+use crate::solver::{Chain, ChainNode, SolverStep};
+
+/// the kinds of houses, as found by dividing a house index by 9
+const ROW: usize = 0;
+const COL: usize = 1;
+const BOX: usize = 2;
+
+/// a chain of one digit with alternating links, and the cells it removes the digit from
+struct Found {
+    digit: u8,
+    /// the groups of cells that make up the chain, the first link is strong
+    nodes: Vec<Bitboard>,
+    eliminations: Bitboard,
+}
+
+impl Found {
+    fn chain(&self) -> Chain {
+        Chain::alternating(
+            self.nodes
+                .iter()
+                .map(|&cells| ChainNode::group(cells, self.digit))
+                .collect(),
+        )
+    }
+
+    fn apply(self, candidates: &mut Candidates) -> bool {
+        candidates.eliminate(self.digit, self.eliminations)
+    }
+}
+
+/// the first pattern `find` finds for any digit
+fn find_any(
+    candidates: &Candidates,
+    find: impl Fn(Bitboard) -> Option<(Vec<Bitboard>, Bitboard)>,
+) -> Option<Found> {
+    (1..=9u8).find_map(|digit| {
+        find(candidates.digit(digit)).map(|(nodes, eliminations)| Found {
+            digit,
+            nodes,
+            eliminations,
+        })
+    })
+}
+
+/// a house where the digit has exactly two cells, seen from one of them
+#[derive(Debug, Clone, Copy)]
+struct StrongLink {
+    house: usize,
+    from: usize,
+    to: usize,
+}
+
+impl StrongLink {
+    fn kind(&self) -> usize {
+        self.house / 9
+    }
+}
+
+/// the strong links of a digit with the given candidate cells, in both directions
+fn strong_links(cells: Bitboard) -> Vec<StrongLink> {
+    let mut links = vec![];
+    for (house, &house_cells) in HOUSES.iter().enumerate() {
+        let pair = cells & house_cells;
+        if pair.count() == 2 {
+            let mut ends = pair.iter();
+            let (a, b) = (ends.next().unwrap(), ends.next().unwrap());
+            links.push(StrongLink {
+                house,
+                from: a,
+                to: b,
+            });
+            links.push(StrongLink {
+                house,
+                from: b,
+                to: a,
+            });
+        }
+    }
+
+    links
+}
+
+/// chains `a=b-c=d` of two strong links joined by a weak one that eliminate something, for
+/// which `accept` returns true
+fn two_link_chains(
+    cells: Bitboard,
+    accept: impl Fn(&StrongLink, &StrongLink) -> bool,
+) -> Option<(Vec<Bitboard>, Bitboard)> {
+    let links = strong_links(cells);
+    for first in &links {
+        for second in &links {
+            let chain = [first.from, first.to, second.from, second.to];
+            let distinct = (0..4).all(|i| (i + 1..4).all(|j| chain[i] != chain[j]));
+            if first.house == second.house
+                || !distinct
+                || !PEERS[first.to].contains(second.from)
+                || !accept(first, second)
+            {
+                continue;
+            }
+
+            let nodes: Vec<Bitboard> = chain.iter().map(|&cell| Bitboard::cell(cell)).collect();
+            let used = nodes.iter().fold(Bitboard::EMPTY, |all, &node| all | node);
+            let eliminations = cells & PEERS[first.from] & PEERS[second.to] & !used;
+            if !eliminations.is_empty() {
+                return Some((nodes, eliminations));
+            }
+        }
+    }
+
+    None
+}
+
+/// two strong links in parallel rows (or columns) with one end of each in the same column (or
+/// row): one of the two other ends holds the digit
+#[derive(Debug)]
+pub struct SkyscraperStep;
+
+fn skyscraper(candidates: &Candidates) -> Option<Found> {
+    find_any(candidates, |cells| {
+        two_link_chains(cells, |first, second| {
+            let kind = first.kind();
+            let base = if kind == ROW { COL } else { ROW };
+            kind != BOX
+                && second.kind() == kind
+                && houses_of(first.to)[base] == houses_of(second.from)[base]
+        })
+    })
+}
+
+impl SolverStep for SkyscraperStep {
+    fn name(&self) -> &'static str {
+        "Skyscraper"
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        skyscraper(candidates).is_some_and(|found| found.apply(candidates))
+    }
+
+    fn chain(&self, candidates: &Candidates) -> Option<Chain> {
+        skyscraper(candidates).map(|found| found.chain())
+    }
+}
+
+/// a strong link in a row and one in a column, with one end of each in the same box: one of
+/// the two other ends holds the digit
+#[derive(Debug)]
+pub struct TwoStringKiteStep;
+
+fn two_string_kite(candidates: &Candidates) -> Option<Found> {
+    find_any(candidates, |cells| {
+        two_link_chains(cells, |first, second| {
+            first.kind() == ROW
+                && second.kind() == COL
+                && houses_of(first.to)[BOX] == houses_of(second.from)[BOX]
+        })
+    })
+}
+
+impl SolverStep for TwoStringKiteStep {
+    fn name(&self) -> &'static str {
+        "2-String Kite"
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        two_string_kite(candidates).is_some_and(|found| found.apply(candidates))
+    }
+
+    fn chain(&self, candidates: &Candidates) -> Option<Chain> {
+        two_string_kite(candidates).map(|found| found.chain())
+    }
+}
+
+/// any two strong links joined by a weak one, which includes skyscrapers and kites as well as
+/// the patterns that use a strong link in a box
+#[derive(Debug)]
+pub struct TurbotFishStep;
+
+fn turbot_fish(candidates: &Candidates) -> Option<Found> {
+    find_any(candidates, |cells| two_link_chains(cells, |_, _| true))
+}
+
+impl SolverStep for TurbotFishStep {
+    fn name(&self) -> &'static str {
+        "Turbot Fish"
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        turbot_fish(candidates).is_some_and(|found| found.apply(candidates))
+    }
+
+    fn chain(&self, candidates: &Candidates) -> Option<Chain> {
+        turbot_fish(candidates).map(|found| found.chain())
+    }
+}
+
+/// a box where the digit is confined to one row and one column, together with a strong link in
+/// a column (or row) that has one end in that row (or column): the digit can't be where the
+/// other end of the link meets the column (or row) of the box
+#[derive(Debug)]
+pub struct EmptyRectangleStep;
+
+fn empty_rectangle(candidates: &Candidates) -> Option<Found> {
+    find_any(candidates, |cells| {
+        for sbox in 0..9 {
+            let box_cells = cells & HOUSES[18 + sbox];
+            if box_cells.count() < 2 {
+                continue;
+            }
+
+            let rows = (sbox / 3) * 3..(sbox / 3) * 3 + 3;
+            let cols = (sbox % 3) * 3..(sbox % 3) * 3 + 3;
+            for row in rows.clone() {
+                for col in cols.clone() {
+                    let (row_cells, col_cells) = (HOUSES[row], HOUSES[9 + col]);
+                    let in_row = box_cells & row_cells;
+                    let in_col = box_cells & col_cells;
+                    if in_row | in_col != box_cells || in_row == box_cells || in_col == box_cells {
+                        continue;
+                    }
+
+                    // a strong link in another column with one end in the row of the rectangle,
+                    // then the other way around
+                    for link in strong_links(cells) {
+                        let (from_row, from_col) = (link.from / 9, link.from % 9);
+                        let (to_row, to_col) = (link.to / 9, link.to % 9);
+                        let target = if link.kind() == COL
+                            && from_row == row
+                            && !cols.contains(&from_col)
+                            && !rows.contains(&to_row)
+                        {
+                            to_row * 9 + col
+                        } else if link.kind() == ROW
+                            && from_col == col
+                            && !rows.contains(&from_row)
+                            && !cols.contains(&to_col)
+                        {
+                            row * 9 + to_col
+                        } else {
+                            continue;
+                        };
+
+                        if cells.contains(target) {
+                            // the end in the rectangle's line sees the part of the box in that
+                            // line, so the box has to use its other line
+                            let (near, far) = if link.kind() == COL {
+                                (in_row, in_col & !in_row)
+                            } else {
+                                (in_col, in_row & !in_col)
+                            };
+                            let nodes = vec![
+                                Bitboard::cell(link.to),
+                                Bitboard::cell(link.from),
+                                near,
+                                far,
+                            ];
+                            return Some((nodes, Bitboard::cell(target)));
+                        }
+                    }
+                }
+            }
+        }
+
+        None
+    })
+}
+
+impl SolverStep for EmptyRectangleStep {
+    fn name(&self) -> &'static str {
+        "Empty Rectangle"
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        empty_rectangle(candidates).is_some_and(|found| found.apply(candidates))
+    }
+
+    fn chain(&self, candidates: &Candidates) -> Option<Chain> {
+        empty_rectangle(candidates).map(|found| found.chain())
+    }
+}
+
+/// a chain of any length with alternating strong and weak links between single cells. the
+/// shortest chain that eliminates something is used.
+#[derive(Debug)]
+pub struct XChainStep;
+
+fn x_chain(candidates: &Candidates) -> Option<Found> {
+    find_any(candidates, |cells| {
+        let mut strong = [Bitboard::EMPTY; 81];
+        for link in strong_links(cells) {
+            strong[link.from].insert(link.to);
+        }
+
+        for start in cells {
+            // breadth first over (cell, whether the next link has to be strong), so shorter
+            // chains are found first
+            let mut parent = [[None; 81]; 2];
+            let mut seen = [Bitboard::EMPTY; 2];
+            let mut queue = std::collections::VecDeque::from([(start, 1, 1usize)]);
+            seen[1].insert(start);
+
+            while let Some((cell, strong_next, length)) = queue.pop_front() {
+                let next = if strong_next == 1 {
+                    strong[cell]
+                } else {
+                    cells & PEERS[cell]
+                };
+
+                for end in next {
+                    let parity = 1 - strong_next;
+                    if end == start || seen[parity].contains(end) {
+                        continue;
+                    }
+                    seen[parity].insert(end);
+                    parent[parity][end] = Some(cell);
+                    queue.push_back((end, parity, length + 1));
+
+                    if strong_next == 0 || length < 3 {
+                        continue;
+                    }
+
+                    // walk back to the start, alternating between the two layers
+                    let mut path = vec![end];
+                    let (mut at, mut layer) = (end, parity);
+                    while let Some(previous) = parent[layer][at] {
+                        path.push(previous);
+                        (at, layer) = (previous, 1 - layer);
+                        if previous == start && layer == 1 {
+                            break;
+                        }
+                    }
+                    path.reverse();
+
+                    let used = path
+                        .iter()
+                        .fold(Bitboard::EMPTY, |all, &cell| all | Bitboard::cell(cell));
+                    if used.count() as usize != path.len() || path[0] != start {
+                        continue;
+                    }
+
+                    let eliminations = cells & PEERS[start] & PEERS[end] & !used;
+                    if !eliminations.is_empty() {
+                        let nodes = path.into_iter().map(Bitboard::cell).collect();
+                        return Some((nodes, eliminations));
+                    }
+                }
+            }
+        }
+
+        None
+    })
+}
+
+impl SolverStep for XChainStep {
+    fn name(&self) -> &'static str {
+        "X-Chain"
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        x_chain(candidates).is_some_and(|found| found.apply(candidates))
+    }
+
+    fn chain(&self, candidates: &Candidates) -> Option<Chain> {
+        x_chain(candidates).map(|found| found.chain())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::techniques::test_util::{cells, eliminated, restricted};
+
+    const DIGIT: u8 = 1;
+
+    #[test]
+    fn finds_skyscraper() {
+        let candidates = restricted(DIGIT, &[(0, &[(0, 1), (0, 4)]), (4, &[(4, 1), (4, 5)])]);
+        assert_eq!(
+            cells(&[(1, 5), (2, 5), (3, 4), (5, 4)]),
+            eliminated(&SkyscraperStep, &candidates, DIGIT)
+        );
+        assert_eq!(
+            "(1)r1c5=r1c2-r5c2=r5c6",
+            SkyscraperStep.chain(&candidates).unwrap().to_string()
+        );
+        assert!(!TwoStringKiteStep.apply_candidates(&mut candidates.clone()));
+    }
+
+    #[test]
+    fn finds_two_string_kite() {
+        let candidates = restricted(DIGIT, &[(0, &[(0, 1), (0, 7)]), (9, &[(2, 0), (6, 0)])]);
+        assert_eq!(
+            cells(&[(6, 7)]),
+            eliminated(&TwoStringKiteStep, &candidates, DIGIT)
+        );
+        // a kite is a turbot fish as well
+        assert_eq!(
+            cells(&[(6, 7)]),
+            eliminated(&TurbotFishStep, &candidates, DIGIT)
+        );
+        assert!(!SkyscraperStep.apply_candidates(&mut candidates.clone()));
+    }
+
+    #[test]
+    fn finds_empty_rectangle() {
+        let candidates = restricted(
+            DIGIT,
+            &[
+                (22, &[(4, 3), (4, 5), (3, 4), (5, 4)]),
+                (17, &[(4, 8), (0, 8)]),
+            ],
+        );
+        assert_eq!(
+            cells(&[(0, 4)]),
+            eliminated(&EmptyRectangleStep, &candidates, DIGIT)
+        );
+        assert_eq!(
+            "(1)r1c9=r5c9-r5c46=r46c5",
+            EmptyRectangleStep.chain(&candidates).unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn finds_long_x_chain() {
+        let candidates = restricted(
+            DIGIT,
+            &[
+                (0, &[(0, 0), (0, 4)]),
+                (3, &[(3, 4), (3, 8)]),
+                (6, &[(6, 8), (6, 1)]),
+            ],
+        );
+        assert!(!TurbotFishStep.apply_candidates(&mut candidates.clone()));
+        assert_eq!(
+            cells(&[(1, 1), (2, 1), (7, 0), (8, 0)]),
+            eliminated(&XChainStep, &candidates, DIGIT)
+        );
+        assert_eq!(6, XChainStep.chain(&candidates).unwrap().nodes.len());
+    }
+}
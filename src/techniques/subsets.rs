@@ -0,0 +1,193 @@
+//! subsets: `n` cells of a house that hold only `n` candidates between them (naked), or `n`
+//! candidates of a house that only fit into `n` of its cells (hidden). either way those
+//! candidates fill those cells, so the candidates are gone from the rest of the house, and for
+//! a hidden subset every other candidate is gone from the cells.
+
+use crate::bitboard::{Bitboard, Candidates, HOUSES};
+use crate::solver::{PatternPart, SolverStep};
+use crate::techniques::subsets;
+
+/// the cells and candidates of a subset, and the candidates it removes
+struct Subset {
+    cells: Bitboard,
+    notes: u16,
+    eliminations: Vec<(u8, Bitboard)>,
+}
+
+impl Subset {
+    fn apply(self, candidates: &mut Candidates) -> bool {
+        self.eliminations
+            .into_iter()
+            .fold(false, |changed, (digit, cells)| {
+                candidates.eliminate(digit, cells) | changed
+            })
+    }
+
+    fn pattern(self) -> Vec<PatternPart> {
+        vec![PatternPart::new("subset", self.cells, self.notes)]
+    }
+}
+
+/// `size` cells of a house with only `size` candidates between them. the size is 2, 3 or 4.
+#[derive(Debug)]
+pub struct NakedSubsetStep(pub usize);
+
+fn naked_subset(candidates: &Candidates, size: usize) -> Option<Subset> {
+    for house in HOUSES {
+        let cells: Vec<usize> = (house & candidates.unsolved())
+            .iter()
+            .filter(|&cell| (2..=size).contains(&(candidates.cell(cell).count_ones() as usize)))
+            .collect();
+
+        let found = subsets(&cells, size, &mut vec![], &mut |chosen| {
+            let notes = chosen
+                .iter()
+                .fold(0, |notes, &cell| notes | candidates.cell(cell));
+            if notes.count_ones() as usize != size {
+                return None;
+            }
+
+            let subset = chosen
+                .iter()
+                .fold(Bitboard::EMPTY, |all, &cell| all | Bitboard::cell(cell));
+            let eliminations: Vec<(u8, Bitboard)> = (1..=9u8)
+                .filter(|&digit| notes & (1 << (digit - 1)) != 0)
+                .map(|digit| (digit, candidates.digit(digit) & house & !subset))
+                .filter(|(_, cells)| !cells.is_empty())
+                .collect();
+            (!eliminations.is_empty()).then_some(Subset {
+                cells: subset,
+                notes,
+                eliminations,
+            })
+        });
+        if found.is_some() {
+            return found;
+        }
+    }
+
+    None
+}
+
+impl SolverStep for NakedSubsetStep {
+    fn name(&self) -> &'static str {
+        match self.0 {
+            2 => "Naked Pair",
+            3 => "Naked Triple",
+            _ => "Naked Quad",
+        }
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        naked_subset(candidates, self.0).is_some_and(|subset| subset.apply(candidates))
+    }
+
+    fn pattern(&self, candidates: &Candidates) -> Vec<PatternPart> {
+        naked_subset(candidates, self.0).map_or(vec![], Subset::pattern)
+    }
+}
+
+/// `size` candidates of a house that only fit into `size` of its cells. the size is 2, 3 or 4.
+#[derive(Debug)]
+pub struct HiddenSubsetStep(pub usize);
+
+fn hidden_subset(candidates: &Candidates, size: usize) -> Option<Subset> {
+    for house in HOUSES {
+        let digits: Vec<usize> = (1..=9)
+            .filter(|&digit| {
+                let places = candidates.digit(digit as u8) & house;
+                (2..=size).contains(&(places.count() as usize))
+            })
+            .collect();
+
+        let found = subsets(&digits, size, &mut vec![], &mut |chosen| {
+            let cells = chosen.iter().fold(Bitboard::EMPTY, |cells, &digit| {
+                cells | (candidates.digit(digit as u8) & house)
+            });
+            if cells.count() as usize != size {
+                return None;
+            }
+
+            let notes = chosen
+                .iter()
+                .fold(0u16, |notes, &digit| notes | (1 << (digit - 1)));
+            let eliminations: Vec<(u8, Bitboard)> = (1..=9u8)
+                .filter(|&digit| notes & (1 << (digit - 1)) == 0)
+                .map(|digit| (digit, candidates.digit(digit) & cells))
+                .filter(|(_, cells)| !cells.is_empty())
+                .collect();
+            (!eliminations.is_empty()).then_some(Subset {
+                cells,
+                notes,
+                eliminations,
+            })
+        });
+        if found.is_some() {
+            return found;
+        }
+    }
+
+    None
+}
+
+impl SolverStep for HiddenSubsetStep {
+    fn name(&self) -> &'static str {
+        match self.0 {
+            2 => "Hidden Pair",
+            3 => "Hidden Triple",
+            _ => "Hidden Quad",
+        }
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        hidden_subset(candidates, self.0).is_some_and(|subset| subset.apply(candidates))
+    }
+
+    fn pattern(&self, candidates: &Candidates) -> Vec<PatternPart> {
+        hidden_subset(candidates, self.0).map_or(vec![], Subset::pattern)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::techniques::test_util::with_notes;
+
+    #[test]
+    fn finds_naked_triple() {
+        let candidates = with_notes(&[(0, 0, &[1, 2]), (0, 4, &[2, 3]), (0, 8, &[1, 3])]);
+        assert!(!NakedSubsetStep(2).apply_candidates(&mut candidates.clone()));
+
+        let mut after = candidates;
+        assert!(NakedSubsetStep(3).apply_candidates(&mut after));
+        for digit in 1..=3 {
+            assert_eq!(
+                HOUSES[0] & !(Bitboard::cell(0) | Bitboard::cell(4) | Bitboard::cell(8)),
+                candidates.digit(digit) & !after.digit(digit)
+            );
+        }
+        assert_eq!(
+            vec!["subset r1c159 {123}"],
+            NakedSubsetStep(3)
+                .pattern(&candidates)
+                .iter()
+                .map(|part| part.to_string())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn finds_hidden_pair() {
+        let mut candidates = with_notes(&[]);
+        let pair = Bitboard::cell(3) | Bitboard::cell(5);
+        for digit in [4, 7] {
+            candidates.eliminate(digit, HOUSES[0] & !pair);
+        }
+
+        let mut after = candidates;
+        assert!(HiddenSubsetStep(2).apply_candidates(&mut after));
+        assert_eq!(0b1001000, after.cell(3));
+        assert_eq!(0b1001000, after.cell(5));
+        assert_eq!(candidates.digit(1) & !pair, after.digit(1));
+    }
+}
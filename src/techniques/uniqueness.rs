@@ -0,0 +1,716 @@
+//! techniques that rely on the puzzle having a unique solution.
+//!
+//! four cells in two rows, two columns and two boxes that all end up with the same two digits
+//! form a deadly pattern: the digits could be swapped, giving a second solution. a puzzle with
+//! one solution can't get there, so whatever would lead to it is false. none of these steps
+//! are used by a [`Solver`](crate::solver::Solver) unless it assumes uniqueness.
+
+use crate::bitboard::{houses_of, Bitboard, Candidates, HOUSES, PEERS};
+use crate::solver::{PatternPart, SolverStep};
+use crate::techniques::{bit, count, digits, subsets};
+
+/// what a step found, and the pattern behind it
+struct Found {
+    placements: Vec<(usize, u8)>,
+    eliminations: Vec<(u8, Bitboard)>,
+    pattern: Vec<PatternPart>,
+}
+
+impl Found {
+    fn eliminating(eliminations: Vec<(u8, Bitboard)>, pattern: Vec<PatternPart>) -> Option<Self> {
+        eliminations
+            .iter()
+            .any(|(_, cells)| !cells.is_empty())
+            .then_some(Found {
+                placements: vec![],
+                eliminations,
+                pattern,
+            })
+    }
+
+    fn apply(self, candidates: &mut Candidates) -> bool {
+        let mut changed = false;
+        for (cell, digit) in self.placements {
+            candidates.place(cell, digit);
+            changed = true;
+        }
+        for (digit, cells) in self.eliminations {
+            changed |= candidates.eliminate(digit, cells);
+        }
+
+        changed
+    }
+}
+
+/// the corners of the rectangles that span two boxes, as `[r1c1, r1c2, r2c1, r2c2]`, so
+/// diagonal corners add up to 3
+fn rectangles() -> impl Iterator<Item = [usize; 4]> {
+    (0..9).flat_map(|r1| {
+        (r1 + 1..9).flat_map(move |r2| {
+            (0..9).flat_map(move |c1| {
+                (c1 + 1..9)
+                    .filter(move |&c2| (r1 / 3 == r2 / 3) != (c1 / 3 == c2 / 3))
+                    .map(move |c2| [r1 * 9 + c1, r1 * 9 + c2, r2 * 9 + c1, r2 * 9 + c2])
+            })
+        })
+    })
+}
+
+/// the houses both cells are in
+fn common_houses(a: usize, b: usize) -> impl Iterator<Item = usize> {
+    let houses_b = houses_of(b);
+    houses_of(a)
+        .into_iter()
+        .filter(move |house| houses_b.contains(house))
+}
+
+fn cells_of(corners: &[usize]) -> Bitboard {
+    corners
+        .iter()
+        .fold(Bitboard::EMPTY, |all, &cell| all | Bitboard::cell(cell))
+}
+
+/// a rectangle of unsolved cells that all have the digits `pair`, with the corners that have
+/// more candidates than those
+struct Rectangle {
+    corners: [usize; 4],
+    pair: u16,
+    extra: Vec<usize>,
+}
+
+impl Rectangle {
+    fn part(&self) -> PatternPart {
+        PatternPart::new("rectangle", cells_of(&self.corners), self.pair)
+    }
+
+    /// the two digits of the pair, the first one `first`
+    fn ordered(&self, first: u8) -> (u8, u8) {
+        (first, digits(self.pair & !bit(first)).next().unwrap())
+    }
+}
+
+/// calls `find` with every rectangle of unsolved cells that share two candidates
+fn find_rectangle(
+    candidates: &Candidates,
+    find: impl Fn(&Rectangle) -> Option<Found>,
+) -> Option<Found> {
+    let unsolved = candidates.unsolved();
+    rectangles().find_map(|corners| {
+        if !corners.iter().all(|&cell| unsolved.contains(cell)) {
+            return None;
+        }
+
+        let common = corners
+            .iter()
+            .fold(0x1ff, |common, &cell| common & candidates.cell(cell));
+        let shared: Vec<u8> = digits(common).collect();
+        (0..shared.len()).find_map(|i| {
+            (i + 1..shared.len()).find_map(|j| {
+                let pair = bit(shared[i]) | bit(shared[j]);
+                let extra = corners
+                    .iter()
+                    .copied()
+                    .filter(|&cell| candidates.cell(cell) != pair)
+                    .collect();
+                find(&Rectangle {
+                    corners,
+                    pair,
+                    extra,
+                })
+            })
+        })
+    })
+}
+
+/// the variants of the unique rectangle
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RectangleType {
+    /// one corner has extra candidates: it can't be either digit of the pair
+    One,
+    /// two corners in a line have the same one extra candidate: it is in one of them
+    Two,
+    /// two corners in a line have extra candidates that form a naked subset with other cells
+    /// of a house they share
+    Three,
+    /// two corners in a line, and one digit of the pair has no other place in a house they
+    /// share: the other digit can't be in them
+    Four,
+    /// two diagonal or three corners have the same one extra candidate: it is in one of them
+    Five,
+    /// two diagonal corners have extra candidates, and one digit of the pair is confined to
+    /// the rectangle in both rows or both columns: it can't be in those two corners
+    Six,
+    /// a corner opposite one without extra candidates, whose row and column have no other
+    /// place for one digit of the pair: it can't be the other digit
+    Hidden,
+}
+
+/// a unique rectangle of the given type
+#[derive(Debug, Clone, Copy)]
+pub struct UniqueRectangleStep(pub RectangleType);
+
+fn same_line(a: usize, b: usize) -> bool {
+    a / 9 == b / 9 || a % 9 == b % 9
+}
+
+/// the digits of the corners besides the pair
+fn extra_digits(candidates: &Candidates, rectangle: &Rectangle) -> u16 {
+    rectangle
+        .extra
+        .iter()
+        .fold(0, |all, &cell| all | candidates.cell(cell))
+        & !rectangle.pair
+}
+
+/// the cells that see all of the cells
+fn seen_by_all(cells: &[usize]) -> Bitboard {
+    cells
+        .iter()
+        .fold(Bitboard::ALL, |seen, &cell| seen & PEERS[cell])
+}
+
+fn type_one(_: &Candidates, rectangle: &Rectangle) -> Option<Found> {
+    let [corner] = rectangle.extra[..] else {
+        return None;
+    };
+    Found::eliminating(
+        digits(rectangle.pair)
+            .map(|digit| (digit, Bitboard::cell(corner)))
+            .collect(),
+        vec![rectangle.part()],
+    )
+}
+
+/// types 2 and 5, where all corners with extra candidates have the same single one
+fn single_extra(
+    candidates: &Candidates,
+    rectangle: &Rectangle,
+    accept: impl Fn(&[usize]) -> bool,
+) -> Option<Found> {
+    let extra = extra_digits(candidates, rectangle);
+    if count(extra) != 1
+        || !rectangle
+            .extra
+            .iter()
+            .all(|&cell| count(candidates.cell(cell)) == 3)
+        || !accept(&rectangle.extra)
+    {
+        return None;
+    }
+
+    let digit = digits(extra).next().unwrap();
+    Found::eliminating(
+        vec![(
+            digit,
+            candidates.digit(digit) & seen_by_all(&rectangle.extra),
+        )],
+        vec![
+            rectangle.part(),
+            PatternPart::new("extra", cells_of(&rectangle.extra), extra),
+        ],
+    )
+}
+
+fn type_two(candidates: &Candidates, rectangle: &Rectangle) -> Option<Found> {
+    single_extra(candidates, rectangle, |extra| {
+        extra.len() == 2 && same_line(extra[0], extra[1])
+    })
+}
+
+fn type_five(candidates: &Candidates, rectangle: &Rectangle) -> Option<Found> {
+    single_extra(candidates, rectangle, |extra| {
+        extra.len() == 3 || (extra.len() == 2 && !same_line(extra[0], extra[1]))
+    })
+}
+
+fn type_three(candidates: &Candidates, rectangle: &Rectangle) -> Option<Found> {
+    let [a, b] = rectangle.extra[..] else {
+        return None;
+    };
+    if !same_line(a, b) {
+        return None;
+    }
+
+    // the two corners act as one cell with the extra digits, which forms a naked subset with
+    // one to three other cells of a house they share
+    let extra = extra_digits(candidates, rectangle);
+    let corners = Bitboard::cell(a) | Bitboard::cell(b);
+    for house in common_houses(a, b) {
+        let others: Vec<usize> = (HOUSES[house] & candidates.unsolved() & !corners)
+            .iter()
+            .filter(|&cell| count(candidates.cell(cell)) <= 4)
+            .collect();
+
+        for size in 1..=3 {
+            let found = subsets(&others, size, &mut vec![], &mut |cells| {
+                let subset = cells
+                    .iter()
+                    .fold(extra, |all, &cell| all | candidates.cell(cell));
+                if count(subset) != size + 1 {
+                    return None;
+                }
+
+                let rest = HOUSES[house] & !corners & !cells_of(cells);
+                Found::eliminating(
+                    digits(subset)
+                        .map(|digit| (digit, candidates.digit(digit) & rest))
+                        .collect(),
+                    vec![
+                        rectangle.part(),
+                        PatternPart::new("extra", corners, extra),
+                        PatternPart::new("subset", cells_of(cells), subset),
+                    ],
+                )
+            });
+            if found.is_some() {
+                return found;
+            }
+        }
+    }
+
+    None
+}
+
+fn type_four(candidates: &Candidates, rectangle: &Rectangle) -> Option<Found> {
+    let [a, b] = rectangle.extra[..] else {
+        return None;
+    };
+    if !same_line(a, b) {
+        return None;
+    }
+
+    let corners = Bitboard::cell(a) | Bitboard::cell(b);
+    digits(rectangle.pair).find_map(|digit| {
+        let (locked, other) = rectangle.ordered(digit);
+        common_houses(a, b)
+            .find(|&house| candidates.digit(locked) & HOUSES[house] == corners)
+            .and_then(|_| {
+                Found::eliminating(
+                    vec![(other, corners)],
+                    vec![
+                        rectangle.part(),
+                        PatternPart::new("strong link", corners, bit(locked)),
+                    ],
+                )
+            })
+    })
+}
+
+fn type_six(candidates: &Candidates, rectangle: &Rectangle) -> Option<Found> {
+    let [a, b] = rectangle.extra[..] else {
+        return None;
+    };
+    if same_line(a, b) {
+        return None;
+    }
+
+    let [top_left, top_right, bottom_left, _] = rectangle.corners;
+    let rectangle_cells = cells_of(&rectangle.corners);
+    let rows = [HOUSES[top_left / 9], HOUSES[bottom_left / 9]];
+    let cols = [HOUSES[9 + top_left % 9], HOUSES[9 + top_right % 9]];
+    digits(rectangle.pair).find_map(|digit| {
+        let confined = |lines: [Bitboard; 2]| {
+            lines
+                .iter()
+                .all(|&line| candidates.digit(digit) & line == rectangle_cells & line)
+        };
+        if !confined(rows) && !confined(cols) {
+            return None;
+        }
+
+        Found::eliminating(
+            vec![(digit, Bitboard::cell(a) | Bitboard::cell(b))],
+            vec![
+                rectangle.part(),
+                PatternPart::new("x-wing", rectangle_cells, bit(digit)),
+            ],
+        )
+    })
+}
+
+fn hidden(candidates: &Candidates, rectangle: &Rectangle) -> Option<Found> {
+    if rectangle.extra.len() < 2 {
+        return None;
+    }
+
+    let rectangle_cells = cells_of(&rectangle.corners);
+    (0..4).find_map(|i| {
+        // the corner without extra candidates and the one diagonally opposite
+        let (plain, opposite) = (rectangle.corners[i], rectangle.corners[3 - i]);
+        if candidates.cell(plain) != rectangle.pair {
+            return None;
+        }
+
+        let row = HOUSES[opposite / 9];
+        let col = HOUSES[9 + opposite % 9];
+        digits(rectangle.pair).find_map(|digit| {
+            let (locked, other) = rectangle.ordered(digit);
+            let places = candidates.digit(locked);
+            if places & row != rectangle_cells & row || places & col != rectangle_cells & col {
+                return None;
+            }
+
+            Found::eliminating(
+                vec![(other, Bitboard::cell(opposite))],
+                vec![
+                    rectangle.part(),
+                    PatternPart::new("strong links", (row | col) & rectangle_cells, bit(locked)),
+                ],
+            )
+        })
+    })
+}
+
+impl UniqueRectangleStep {
+    fn find(&self, candidates: &Candidates) -> Option<Found> {
+        let find = match self.0 {
+            RectangleType::One => type_one,
+            RectangleType::Two => type_two,
+            RectangleType::Three => type_three,
+            RectangleType::Four => type_four,
+            RectangleType::Five => type_five,
+            RectangleType::Six => type_six,
+            RectangleType::Hidden => hidden,
+        };
+        find_rectangle(candidates, |rectangle| find(candidates, rectangle))
+    }
+}
+
+impl SolverStep for UniqueRectangleStep {
+    fn name(&self) -> &'static str {
+        match self.0 {
+            RectangleType::One => "Unique Rectangle Type 1",
+            RectangleType::Two => "Unique Rectangle Type 2",
+            RectangleType::Three => "Unique Rectangle Type 3",
+            RectangleType::Four => "Unique Rectangle Type 4",
+            RectangleType::Five => "Unique Rectangle Type 5",
+            RectangleType::Six => "Unique Rectangle Type 6",
+            RectangleType::Hidden => "Hidden Unique Rectangle",
+        }
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        self.find(candidates)
+            .is_some_and(|found| found.apply(candidates))
+    }
+
+    fn needs_uniqueness(&self) -> bool {
+        true
+    }
+
+    fn pattern(&self, candidates: &Candidates) -> Vec<PatternPart> {
+        self.find(candidates).map_or(vec![], |found| found.pattern)
+    }
+}
+
+/// a rectangle of cells that were solved rather than given, where the digits would form a
+/// deadly pattern: three solved corners keep the fourth from completing it, and two solved
+/// diagonal corners with the same digit keep the other two from being equal
+#[derive(Debug)]
+pub struct AvoidableRectangleStep;
+
+fn avoidable_rectangle(candidates: &Candidates) -> Option<Found> {
+    let solved = !candidates.unsolved() & !candidates.givens();
+    rectangles().find_map(|corners| {
+        let part = |digits: u16| PatternPart::new("rectangle", cells_of(&corners), digits);
+        (0..4).find_map(|i| {
+            // a solved corner and the one diagonally opposite
+            let (a, d) = (corners[i], corners[3 - i]);
+            let (b, c) = (corners[i ^ 1], corners[i ^ 2]);
+            let value = |cell: usize| candidates.value(cell);
+            if !solved.contains(b) || !solved.contains(c) || value(b) != value(c) {
+                return None;
+            }
+
+            if solved.contains(a) && value(d) == 0 {
+                Found::eliminating(
+                    vec![(value(a), Bitboard::cell(d))],
+                    vec![part(bit(value(a)) | bit(value(b)))],
+                )
+            } else if a < d
+                && value(a) == 0
+                && value(d) == 0
+                && count(candidates.cell(a)) == 2
+                && candidates.cell(a) == candidates.cell(d)
+            {
+                // the two cells can't both be the same, so each digit is in one of them
+                Found::eliminating(
+                    digits(candidates.cell(a))
+                        .map(|digit| (digit, candidates.digit(digit) & PEERS[a] & PEERS[d]))
+                        .collect(),
+                    vec![part(candidates.cell(a) | bit(value(b)))],
+                )
+            } else {
+                None
+            }
+        })
+    })
+}
+
+impl SolverStep for AvoidableRectangleStep {
+    fn name(&self) -> &'static str {
+        "Avoidable Rectangle"
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        avoidable_rectangle(candidates).is_some_and(|found| found.apply(candidates))
+    }
+
+    fn needs_uniqueness(&self) -> bool {
+        true
+    }
+
+    fn pattern(&self, candidates: &Candidates) -> Vec<PatternPart> {
+        avoidable_rectangle(candidates).map_or(vec![], |found| found.pattern)
+    }
+}
+
+/// every unsolved cell has two candidates except for one with three, and every digit has two
+/// places in each house it is left in, except the one that has three in the houses of that
+/// cell: without it, the grid would have two solutions, so the cell holds it
+#[derive(Debug)]
+pub struct BugPlusOneStep;
+
+fn bug_plus_one(candidates: &Candidates) -> Option<Found> {
+    let unsolved = candidates.unsolved();
+    let mut triple = None;
+    for cell in unsolved {
+        match count(candidates.cell(cell)) {
+            2 => {}
+            3 if triple.is_none() => triple = Some(cell),
+            _ => return None,
+        }
+    }
+
+    let cell = triple?;
+    let notes = candidates.cell(cell);
+    let digit = digits(notes).find(|&digit| {
+        houses_of(cell)
+            .iter()
+            .all(|&house| (candidates.digit(digit) & HOUSES[house]).count() == 3)
+    })?;
+
+    let balanced = HOUSES.iter().enumerate().all(|(house, &cells)| {
+        (1..=9u8).all(|d| {
+            let places = (candidates.digit(d) & cells).count();
+            places == 0
+                || places == 2
+                || (places == 3 && d == digit && houses_of(cell).contains(&house))
+        })
+    });
+
+    balanced.then(|| Found {
+        placements: vec![(cell, digit)],
+        eliminations: vec![],
+        pattern: vec![PatternPart::new("cell", Bitboard::cell(cell), notes)],
+    })
+}
+
+impl SolverStep for BugPlusOneStep {
+    fn name(&self) -> &'static str {
+        "BUG+1"
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        bug_plus_one(candidates).is_some_and(|found| found.apply(candidates))
+    }
+
+    fn needs_uniqueness(&self) -> bool {
+        true
+    }
+
+    fn pattern(&self, candidates: &Candidates) -> Vec<PatternPart> {
+        bug_plus_one(candidates).map_or(vec![], |found| found.pattern)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solver::Solver;
+    use crate::sudoku::{Game, Numbers};
+    use crate::techniques::test_util::{cells, eliminated, with_notes};
+
+    fn rectangle(kind: RectangleType) -> UniqueRectangleStep {
+        UniqueRectangleStep(kind)
+    }
+
+    #[test]
+    fn finds_type_one() {
+        let candidates = with_notes(&[
+            (0, 0, &[1, 2]),
+            (0, 3, &[1, 2]),
+            (1, 0, &[1, 2]),
+            (1, 3, &[1, 2, 5]),
+        ]);
+        let mut after = candidates;
+        assert!(rectangle(RectangleType::One).apply_candidates(&mut after));
+        assert_eq!(1 << 4, after.cell(12));
+    }
+
+    #[test]
+    fn finds_types_two_and_five() {
+        let candidates = with_notes(&[
+            (0, 0, &[1, 2]),
+            (0, 3, &[1, 2]),
+            (1, 0, &[1, 2, 5]),
+            (1, 3, &[1, 2, 5]),
+        ]);
+        assert_eq!(
+            HOUSES[1] & !cells(&[(1, 0), (1, 3)]),
+            eliminated(&rectangle(RectangleType::Two), &candidates, 5)
+        );
+        assert!(!rectangle(RectangleType::Five).apply_candidates(&mut candidates.clone()));
+
+        let candidates = with_notes(&[
+            (0, 0, &[1, 2]),
+            (0, 3, &[1, 2, 5]),
+            (1, 0, &[1, 2, 5]),
+            (1, 3, &[1, 2, 5]),
+        ]);
+        assert_eq!(
+            cells(&[(1, 4), (1, 5)]),
+            eliminated(&rectangle(RectangleType::Five), &candidates, 5)
+        );
+    }
+
+    #[test]
+    fn finds_type_three() {
+        let candidates = with_notes(&[
+            (0, 0, &[1, 2]),
+            (0, 3, &[1, 2]),
+            (1, 0, &[1, 2, 5]),
+            (1, 3, &[1, 2, 6]),
+            (1, 6, &[5, 6]),
+        ]);
+        let rest = HOUSES[1] & !cells(&[(1, 0), (1, 3), (1, 6)]);
+        let step = rectangle(RectangleType::Three);
+        assert_eq!(rest, eliminated(&step, &candidates, 5));
+        assert_eq!(rest, eliminated(&step, &candidates, 6));
+        assert_eq!(
+            vec![
+                "rectangle r1c1|r1c4|r2c1|r2c4 {12}",
+                "extra r2c14 {56}",
+                "subset r2c7 {56}"
+            ],
+            step.pattern(&candidates)
+                .iter()
+                .map(|part| part.to_string())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn finds_type_four() {
+        let mut candidates = with_notes(&[
+            (0, 0, &[1, 2]),
+            (0, 3, &[1, 2]),
+            (1, 0, &[1, 2, 5]),
+            (1, 3, &[1, 2, 6]),
+        ]);
+        candidates.eliminate(1, HOUSES[1] & !cells(&[(1, 0), (1, 3)]));
+        assert_eq!(
+            cells(&[(1, 0), (1, 3)]),
+            eliminated(&rectangle(RectangleType::Four), &candidates, 2)
+        );
+    }
+
+    #[test]
+    fn finds_type_six() {
+        let mut candidates = with_notes(&[
+            (0, 0, &[1, 2, 5]),
+            (0, 3, &[1, 2]),
+            (1, 0, &[1, 2]),
+            (1, 3, &[1, 2, 6]),
+        ]);
+        candidates.eliminate(
+            1,
+            (HOUSES[0] | HOUSES[1]) & !cells(&[(0, 0), (0, 3), (1, 0), (1, 3)]),
+        );
+        assert_eq!(
+            cells(&[(0, 0), (1, 3)]),
+            eliminated(&rectangle(RectangleType::Six), &candidates, 1)
+        );
+    }
+
+    #[test]
+    fn finds_hidden_rectangle() {
+        let mut candidates = with_notes(&[
+            (0, 0, &[1, 2]),
+            (0, 3, &[1, 2, 5]),
+            (1, 0, &[1, 2, 6]),
+            (1, 3, &[1, 2, 7]),
+        ]);
+        candidates.eliminate(1, HOUSES[1] & !cells(&[(1, 0), (1, 3)]));
+        candidates.eliminate(1, HOUSES[12] & !cells(&[(0, 3), (1, 3)]));
+        assert_eq!(
+            cells(&[(1, 3)]),
+            eliminated(&rectangle(RectangleType::Hidden), &candidates, 2)
+        );
+    }
+
+    #[test]
+    fn finds_avoidable_rectangle_only_on_solved_cells() {
+        let mut candidates = Candidates::from_numbers(&Numbers::empty());
+        candidates.place(0, 1);
+        candidates.place(3, 2);
+        candidates.place(9, 2);
+        assert_eq!(
+            cells(&[(1, 3)]),
+            eliminated(&AvoidableRectangleStep, &candidates, 1)
+        );
+
+        let givens: Numbers = format!("1..2.....2{}", ".".repeat(71)).parse().unwrap();
+        let mut candidates = Candidates::from_numbers(&givens);
+        assert!(!AvoidableRectangleStep.apply_candidates(&mut candidates));
+    }
+
+    /// a grid in the state of a BUG+1, where r1c1 has to be 3
+    fn bug_plus_one_game() -> Game {
+        let mut solution: Vec<char> =
+            "123456789456789123789123456214365897365897214897214365531642978642978531978531642"
+                .chars()
+                .collect();
+        let notes: [(usize, usize, u16); 7] = [
+            (1, 1, 0b111),
+            (1, 2, 0b101),
+            (2, 1, 0b110),
+            (1, 4, 0b110),
+            (4, 1, 0b101),
+            (2, 4, 0b110),
+            (4, 2, 0b101),
+        ];
+        for &(row, col, _) in &notes {
+            solution[(row - 1) * 9 + col - 1] = '.';
+        }
+
+        let numbers: Numbers = solution.into_iter().collect::<String>().parse().unwrap();
+        let mut game = Game::create(numbers);
+        for (row, col, cell_notes) in notes {
+            game.set_notes(row, col, cell_notes);
+        }
+        game
+    }
+
+    #[test]
+    fn finds_bug_plus_one() {
+        let candidates = Candidates::from_game(&bug_plus_one_game());
+        let mut after = candidates;
+        assert!(BugPlusOneStep.apply_candidates(&mut after));
+        assert_eq!(3, after.value(0));
+    }
+
+    #[test]
+    fn solver_only_uses_uniqueness_when_told_to() {
+        let mut solver = Solver::default();
+        solver.add_step(BugPlusOneStep);
+        assert!(solver.solve_log(bug_plus_one_game()).entries.is_empty());
+
+        solver.set_assume_uniqueness(true);
+        let log = solver.solve_log(bug_plus_one_game());
+        assert_eq!(
+            "BUG+1: cell r1c1 {123}; places r1c1=3",
+            log.entries[0].explanation
+        );
+    }
+}
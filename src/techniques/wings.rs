@@ -0,0 +1,310 @@
+//! wings: a few cells that together hold no more digits than there are cells, joined through a
+//! pivot that sees all the other cells (the pincers).
+//!
+//! every digit but one can appear only once among the cells, because all the cells that hold it
+//! see each other. those digits can fill at most one cell each, so the remaining digit has to
+//! be in one of the cells, and it can be removed from every cell that sees all of its places in
+//! the wing.
+
+use crate::bitboard::{Bitboard, Candidates, HOUSES, PEERS};
+use crate::solver::{PatternPart, SolverStep};
+use crate::techniques::{count, subsets};
+
+/// the pattern of a wing and the cells it removes the digit from
+struct Wing {
+    digit: u8,
+    parts: Vec<PatternPart>,
+    eliminations: Bitboard,
+}
+
+impl Wing {
+    fn apply(self, candidates: &mut Candidates) -> bool {
+        candidates.eliminate(self.digit, self.eliminations)
+    }
+}
+
+/// the first wing of `size` cells that eliminates something, for which `accept` returns true
+/// when given the notes of the pivot, the notes of the pincers and the digit to eliminate
+fn find_wing(
+    candidates: &Candidates,
+    size: usize,
+    accept: impl Fn(u16, &[u16], u8) -> bool,
+) -> Option<Wing> {
+    let unsolved = candidates.unsolved();
+    for pivot in unsolved {
+        let pivot_notes = candidates.cell(pivot);
+        if count(pivot_notes) > size {
+            continue;
+        }
+
+        let pincers: Vec<usize> = (PEERS[pivot] & unsolved)
+            .iter()
+            .filter(|&cell| {
+                let notes = candidates.cell(cell);
+                count(notes) >= 2 && count(notes | pivot_notes) <= size
+            })
+            .collect();
+
+        let found = subsets(&pincers, size - 1, &mut vec![], &mut |pincers| {
+            let pincer_notes: Vec<u16> =
+                pincers.iter().map(|&cell| candidates.cell(cell)).collect();
+            let all_notes = pincer_notes
+                .iter()
+                .fold(pivot_notes, |all, &notes| all | notes);
+            if count(all_notes) != size {
+                return None;
+            }
+
+            let cells = pincers.iter().fold(Bitboard::cell(pivot), |all, &cell| {
+                all | Bitboard::cell(cell)
+            });
+            let mut unrestricted = (1..=9u8).filter(|&digit| {
+                let places = cells & candidates.digit(digit);
+                !places.is_empty()
+                    && places
+                        .iter()
+                        .any(|cell| !(places & !Bitboard::cell(cell) & !PEERS[cell]).is_empty())
+            });
+            let digit = unrestricted.next()?;
+            if unrestricted.next().is_some() || !accept(pivot_notes, &pincer_notes, digit) {
+                return None;
+            }
+
+            let eliminations = (cells & candidates.digit(digit))
+                .iter()
+                .fold(candidates.digit(digit) & !cells, |seen, cell| {
+                    seen & PEERS[cell]
+                });
+            if eliminations.is_empty() {
+                return None;
+            }
+
+            let mut parts = vec![PatternPart::new(
+                "pivot",
+                Bitboard::cell(pivot),
+                pivot_notes,
+            )];
+            for (&cell, &notes) in pincers.iter().zip(&pincer_notes) {
+                parts.push(PatternPart::new("pincer", Bitboard::cell(cell), notes));
+            }
+            Some(Wing {
+                digit,
+                parts,
+                eliminations,
+            })
+        });
+        if found.is_some() {
+            return found;
+        }
+    }
+
+    None
+}
+
+fn has_digit(notes: u16, digit: u8) -> bool {
+    notes & (1 << (digit - 1)) != 0
+}
+
+/// a pivot with two candidates xy, seeing pincers xz and yz: one of the pincers is z
+#[derive(Debug)]
+pub struct XyWingStep;
+
+fn xy_wing(candidates: &Candidates) -> Option<Wing> {
+    find_wing(candidates, 3, |pivot, pincers, digit| {
+        count(pivot) == 2
+            && !has_digit(pivot, digit)
+            && pincers.iter().all(|&notes| count(notes) == 2)
+    })
+}
+
+impl SolverStep for XyWingStep {
+    fn name(&self) -> &'static str {
+        "XY-Wing"
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        xy_wing(candidates).is_some_and(|wing| wing.apply(candidates))
+    }
+
+    fn pattern(&self, candidates: &Candidates) -> Vec<PatternPart> {
+        xy_wing(candidates).map_or(vec![], |wing| wing.parts)
+    }
+}
+
+/// a pivot with three candidates xyz, seeing pincers xz and yz: one of the three cells is z
+#[derive(Debug)]
+pub struct XyzWingStep;
+
+fn xyz_wing(candidates: &Candidates) -> Option<Wing> {
+    find_wing(candidates, 3, |pivot, pincers, digit| {
+        count(pivot) == 3
+            && has_digit(pivot, digit)
+            && pincers.iter().all(|&notes| count(notes) == 2)
+    })
+}
+
+impl SolverStep for XyzWingStep {
+    fn name(&self) -> &'static str {
+        "XYZ-Wing"
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        xyz_wing(candidates).is_some_and(|wing| wing.apply(candidates))
+    }
+
+    fn pattern(&self, candidates: &Candidates) -> Vec<PatternPart> {
+        xyz_wing(candidates).map_or(vec![], |wing| wing.parts)
+    }
+}
+
+/// four cells with four candidates between them, a pivot seeing three pincers
+#[derive(Debug)]
+pub struct WxyzWingStep;
+
+fn wxyz_wing(candidates: &Candidates) -> Option<Wing> {
+    find_wing(candidates, 4, |_, _, _| true)
+}
+
+impl SolverStep for WxyzWingStep {
+    fn name(&self) -> &'static str {
+        "WXYZ-Wing"
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        wxyz_wing(candidates).is_some_and(|wing| wing.apply(candidates))
+    }
+
+    fn pattern(&self, candidates: &Candidates) -> Vec<PatternPart> {
+        wxyz_wing(candidates).map_or(vec![], |wing| wing.parts)
+    }
+}
+
+/// two cells with the same candidates xy that don't see each other, joined by a strong link on
+/// x whose ends each see one of them: if neither cell were y, both would be x and the link
+/// would have no x at all, so y can't be where both cells are seen
+#[derive(Debug)]
+pub struct WWingStep;
+
+fn w_wing(candidates: &Candidates) -> Option<Wing> {
+    let unsolved = candidates.unsolved();
+    let pairs: Vec<usize> = unsolved
+        .iter()
+        .filter(|&cell| count(candidates.cell(cell)) == 2)
+        .collect();
+
+    for (i, &a) in pairs.iter().enumerate() {
+        let notes = candidates.cell(a);
+        for &b in &pairs[i + 1..] {
+            if candidates.cell(b) != notes || PEERS[a].contains(b) {
+                continue;
+            }
+
+            let digits: Vec<u8> = (1..=9u8).filter(|&d| has_digit(notes, d)).collect();
+            for (x, y) in [(digits[0], digits[1]), (digits[1], digits[0])] {
+                let eliminations = candidates.digit(y) & PEERS[a] & PEERS[b];
+                if eliminations.is_empty() {
+                    continue;
+                }
+
+                for house in HOUSES {
+                    let link = candidates.digit(x) & house;
+                    if link.count() != 2 || link.contains(a) || link.contains(b) {
+                        continue;
+                    }
+
+                    let mut ends = link.iter();
+                    let (c, d) = (ends.next().unwrap(), ends.next().unwrap());
+                    if (PEERS[a].contains(c) && PEERS[b].contains(d))
+                        || (PEERS[a].contains(d) && PEERS[b].contains(c))
+                    {
+                        return Some(Wing {
+                            digit: y,
+                            parts: vec![
+                                PatternPart::new("pincer", Bitboard::cell(a), notes),
+                                PatternPart::new("pincer", Bitboard::cell(b), notes),
+                                PatternPart::new("strong link", link, 1 << (x - 1)),
+                            ],
+                            eliminations,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    None
+}
+
+impl SolverStep for WWingStep {
+    fn name(&self) -> &'static str {
+        "W-Wing"
+    }
+
+    fn apply_candidates(&self, candidates: &mut Candidates) -> bool {
+        w_wing(candidates).is_some_and(|wing| wing.apply(candidates))
+    }
+
+    fn pattern(&self, candidates: &Candidates) -> Vec<PatternPart> {
+        w_wing(candidates).map_or(vec![], |wing| wing.parts)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::techniques::test_util::{cells, described, eliminated, with_notes};
+
+    #[test]
+    fn finds_xy_wing() {
+        let candidates = with_notes(&[(4, 4, &[1, 2]), (4, 0, &[1, 3]), (0, 4, &[2, 3])]);
+        assert_eq!(cells(&[(0, 0)]), eliminated(&XyWingStep, &candidates, 3));
+        assert_eq!(
+            vec!["pivot r5c5 {12}", "pincer r1c5 {23}", "pincer r5c1 {13}"],
+            described(&XyWingStep, &candidates)
+        );
+        assert!(!XyzWingStep.apply_candidates(&mut candidates.clone()));
+    }
+
+    #[test]
+    fn finds_xyz_wing() {
+        let candidates = with_notes(&[(4, 4, &[1, 2, 3]), (4, 1, &[1, 3]), (3, 4, &[2, 3])]);
+        assert_eq!(
+            cells(&[(4, 3), (4, 5)]),
+            eliminated(&XyzWingStep, &candidates, 3)
+        );
+        assert!(!XyWingStep.apply_candidates(&mut candidates.clone()));
+    }
+
+    #[test]
+    fn finds_wxyz_wing() {
+        let candidates = with_notes(&[
+            (4, 4, &[1, 2, 3, 4]),
+            (4, 0, &[1, 4]),
+            (3, 4, &[2, 4]),
+            (5, 5, &[3, 4]),
+        ]);
+        assert_eq!(
+            cells(&[(4, 3), (4, 5)]),
+            eliminated(&WxyzWingStep, &candidates, 4)
+        );
+        assert!(!XyzWingStep.apply_candidates(&mut candidates.clone()));
+    }
+
+    #[test]
+    fn finds_w_wing() {
+        let mut candidates = with_notes(&[(0, 0, &[1, 2]), (4, 4, &[1, 2])]);
+        candidates.eliminate(1, HOUSES[17] & !cells(&[(0, 8), (4, 8)]));
+        assert_eq!(
+            cells(&[(0, 4), (4, 0)]),
+            eliminated(&WWingStep, &candidates, 2)
+        );
+        assert_eq!(
+            vec![
+                "pincer r1c1 {12}",
+                "pincer r5c5 {12}",
+                "strong link r15c9 {1}"
+            ],
+            described(&WWingStep, &candidates)
+        );
+    }
+}